@@ -1,15 +1,31 @@
 use std::time::Instant;
 
-use crate::{cells::world::WorldCell, serialization::store_world_shallow, state::MState};
+use crate::{
+    cells::{
+        code::Program,
+        disasters::Disaster,
+        organism::{Label, LABEL_SIZE},
+        presets::{ConfigPreset, GradientTarget},
+        world::{SpawnError, WorldCell},
+    },
+    rle::{encode_rle, load_rle},
+    serialization::{
+        dominant_genome, energy_histogram, inspect_organism, lineage_stats, load_field,
+        minerals_histogram, snapshot_registers, store_field_compact, store_genome,
+        store_neighbourhood, store_world_shallow, store_world_sparse,
+    },
+    state::MState,
+};
 
 use actix_web::{
     error,
     error::Error,
     get, post,
-    web::{self, Data, Json, Path},
+    web::{self, Data, Json, Path, Query},
     HttpResponse, Responder,
 };
 use futures::StreamExt;
+use serde::Deserialize;
 
 #[get("/world")]
 pub async fn get_map(state: Data<MState>) -> impl Responder {
@@ -20,6 +36,13 @@ pub async fn get_map(state: Data<MState>) -> impl Responder {
     Json(store_world_shallow(world))
 }
 
+#[get("/world/sparse")]
+pub async fn get_map_sparse(state: Data<MState>) -> impl Responder {
+    let state = state.lock();
+
+    Json(store_world_sparse(&state.world))
+}
+
 #[post("/human")]
 pub async fn set_last_human(state: Data<MState>) -> impl Responder {
     let mut state = state.lock();
@@ -62,24 +85,149 @@ pub async fn inspect(state: Data<MState>, idx: Path<(usize, usize)>) -> impl Res
     }
 }
 
+#[derive(Deserialize)]
+pub struct StatsQuery {
+    #[serde(default)]
+    legacy: bool,
+}
+
+#[derive(Deserialize)]
+pub struct HistogramQuery {
+    #[serde(default = "default_bins")]
+    bins: usize,
+}
+
+fn default_bins() -> usize {
+    10
+}
+
+const MAX_HISTOGRAM_BINS: usize = 1000;
+
+fn validate_bins(bins: usize) -> Result<(), Error> {
+    if bins == 0 || bins > MAX_HISTOGRAM_BINS {
+        return Err(error::ErrorBadRequest(format!(
+            "bins should be in range 1..={}",
+            MAX_HISTOGRAM_BINS
+        )));
+    }
+    Ok(())
+}
+
+#[get("/energy-histogram")]
+pub async fn get_energy_histogram(
+    state: Data<MState>,
+    query: Query<HistogramQuery>,
+) -> Result<HttpResponse, Error> {
+    validate_bins(query.bins)?;
+    let state = state.lock();
+    Ok(HttpResponse::Ok().json(energy_histogram(&state.world, query.bins)))
+}
+
+#[get("/minerals-histogram")]
+pub async fn get_minerals_histogram(
+    state: Data<MState>,
+    query: Query<HistogramQuery>,
+) -> Result<HttpResponse, Error> {
+    validate_bins(query.bins)?;
+    let state = state.lock();
+    Ok(HttpResponse::Ok().json(minerals_histogram(&state.world, query.bins)))
+}
+
+#[get("/inspect-json/{i}/{j}")]
+pub async fn inspect_json(state: Data<MState>, idx: Path<(usize, usize)>) -> impl Responder {
+    let (i, j) = *idx;
+
+    let state = state.lock();
+    match state.world.field.get((i, j)) {
+        Some(WorldCell::Organism(bot)) => HttpResponse::Ok().json(inspect_organism(bot)),
+        Some(_) => HttpResponse::NotFound().body(format!("no organism at ({}, {})", i, j)),
+        None => HttpResponse::NotFound().body(format!("({}, {}) out of bounds", i, j)),
+    }
+}
+
+#[get("/genome/{i}/{j}")]
+pub async fn get_genome(state: Data<MState>, idx: Path<(usize, usize)>) -> impl Responder {
+    let (i, j) = *idx;
+
+    let state = state.lock();
+    match state.world.field.get((i, j)) {
+        Some(WorldCell::Organism(bot)) => HttpResponse::Ok().json(store_genome(bot)),
+        Some(_) => HttpResponse::NotFound().body(format!("no organism at ({}, {})", i, j)),
+        None => HttpResponse::NotFound().body(format!("({}, {}) out of bounds", i, j)),
+    }
+}
+
+#[get("/registers/{i}/{j}")]
+pub async fn get_registers(state: Data<MState>, idx: Path<(usize, usize)>) -> impl Responder {
+    let (i, j) = *idx;
+
+    let state = state.lock();
+    match state.world.field.get((i, j)) {
+        Some(WorldCell::Organism(bot)) => HttpResponse::Ok().json(snapshot_registers(bot)),
+        Some(_) => HttpResponse::NotFound().body(format!("no organism at ({}, {})", i, j)),
+        None => HttpResponse::NotFound().body(format!("({}, {}) out of bounds", i, j)),
+    }
+}
+
+#[get("/memory")]
+pub async fn memory(state: Data<MState>) -> impl Responder {
+    let state = state.lock();
+    Json(state.world.memory_footprint())
+}
+
+#[get("/dominant-genome")]
+pub async fn get_dominant_genome(state: Data<MState>) -> impl Responder {
+    let state = state.lock();
+    match dominant_genome(&state.world) {
+        Some(genome) => HttpResponse::Ok().json(genome),
+        None => HttpResponse::NotFound().body("no living organisms"),
+    }
+}
+
+#[get("/lineage/{id}")]
+pub async fn get_lineage(state: Data<MState>, id: Path<usize>) -> impl Responder {
+    let state = state.lock();
+    match lineage_stats(&state.world, *id) {
+        Some(lineage) => HttpResponse::Ok().json(lineage),
+        None => HttpResponse::NotFound().body(format!("no living organisms of lineage {}", id)),
+    }
+}
+
 #[get("/stats")]
-pub async fn stats(state: Data<MState>) -> impl Responder {
+pub async fn stats(state: Data<MState>, query: Query<StatsQuery>) -> HttpResponse {
     let state = state.lock();
-    let mut stats = state.stats.as_dict();
-    stats.insert(
-        "is_paused",
-        if state.paused { "1" } else { "0" }.to_string(),
-    );
-    Json(stats.clone())
+    if query.legacy {
+        let mut stats = state.stats.as_dict();
+        stats.insert(
+            "is_paused",
+            if state.paused { "1" } else { "0" }.to_string(),
+        );
+        HttpResponse::Ok().json(stats)
+    } else {
+        HttpResponse::Ok().json(state.stats_response())
+    }
+}
+
+#[get("/history")]
+pub async fn history(state: Data<MState>) -> impl Responder {
+    let state = state.lock();
+    Json(state.history.iter().cloned().collect::<Vec<_>>())
+}
+
+#[get("/dashboard")]
+pub async fn dashboard(state: Data<MState>) -> impl Responder {
+    Json(state.lock().dashboard())
 }
 
 #[post("/spawn-random")]
 pub async fn spawn_random(state: Data<MState>, bots: Json<usize>) -> impl Responder {
     let mut state = state.lock();
     let world = &mut state.world;
-    match world.populate_random(bots.0) {
-        Ok(_) => HttpResponse::Created().finish(),
-        Err(n) => HttpResponse::Conflict().body(format!("failed to add {} bots", n)),
+    let result = world.populate_random(bots.0);
+    if result.placed == 0 && result.requested > 0 {
+        HttpResponse::Conflict().json(result)
+    } else {
+        HttpResponse::Created().json(result)
     }
 }
 
@@ -87,9 +235,48 @@ pub async fn spawn_random(state: Data<MState>, bots: Json<usize>) -> impl Respon
 pub async fn spawn_green(state: Data<MState>, bots: Json<usize>) -> impl Responder {
     let mut state = state.lock();
     let world = &mut state.world;
-    match world.populate_green(bots.0) {
-        Ok(_) => HttpResponse::Created().finish(),
-        Err(n) => HttpResponse::Conflict().body(format!("failed to add {} bots", n)),
+    let result = world.populate_green(bots.0);
+    if result.placed == 0 && result.requested > 0 {
+        HttpResponse::Conflict().json(result)
+    } else {
+        HttpResponse::Created().json(result)
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SpawnGenomeRequest {
+    i: usize,
+    j: usize,
+    program: Program,
+    #[serde(default)]
+    label: Option<String>,
+}
+
+#[post("/spawn-genome")]
+pub async fn spawn_genome(
+    state: Data<MState>,
+    request: Json<SpawnGenomeRequest>,
+) -> Result<HttpResponse, Error> {
+    let request = request.into_inner();
+    let label = match request.label {
+        Some(label) => Some(Label::new(&label).ok_or_else(|| {
+            error::ErrorBadRequest(format!("label should be at most {LABEL_SIZE} bytes"))
+        })?),
+        None => None,
+    };
+
+    let mut state = state.lock();
+    match state
+        .world
+        .spawn_program((request.i, request.j), request.program, label)
+    {
+        Ok(()) => Ok(HttpResponse::Created().body("")),
+        Err(SpawnError::OutOfBounds) => Ok(HttpResponse::BadRequest()
+            .body(format!("({}, {}) out of bounds", request.i, request.j))),
+        Err(SpawnError::Occupied) => Ok(HttpResponse::Conflict().body(format!(
+            "({}, {}) is not an empty cell",
+            request.i, request.j
+        ))),
     }
 }
 
@@ -101,6 +288,18 @@ pub async fn tick(state: Data<MState>) -> impl Responder {
     Json(store_world_shallow(world))
 }
 
+#[post("/step-cell/{i}/{j}")]
+pub async fn step_cell(state: Data<MState>, idx: Path<(usize, usize)>) -> impl Responder {
+    let (i, j) = *idx;
+
+    let mut state = state.lock();
+    let world = &mut state.world;
+    match world.step_cell((i, j)) {
+        Ok(_) => HttpResponse::Ok().json(store_neighbourhood(world, (i, j))),
+        Err(_) => HttpResponse::NotFound().body(format!("no organism at ({}, {})", i, j)),
+    }
+}
+
 #[post("/set-config/{key}")]
 pub async fn set_setting(
     state: Data<MState>,
@@ -108,25 +307,47 @@ pub async fn set_setting(
     value: Json<usize>,
 ) -> impl Responder {
     let mut state = state.lock();
-    let key = key.into_inner();
-    let value = value.0;
-    match key.as_str() {
-        "mutation_chance" => {
-            state.world.config.mutation_chance = value;
-        }
-
-        "max_cell_size" => {
-            state.world.config.max_cell_size = value;
-        }
+    match state.world.config.set_setting(&key, value.0) {
+        Ok(()) => HttpResponse::Ok().finish(),
+        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
 
-        "max_minerals" => {
-            state.world.config.max_cell_size = value;
-        }
+#[derive(Deserialize)]
+pub struct GradientQuery {
+    #[serde(rename = "type")]
+    target: GradientTarget,
+    preset: ConfigPreset,
+}
 
-        other => {
-            return HttpResponse::BadRequest().body(format!("parameter not found: {}", other));
-        }
+#[post("/set-flag/{key}")]
+pub async fn set_flag(state: Data<MState>, key: Path<String>, value: Json<bool>) -> impl Responder {
+    let mut state = state.lock();
+    match state.world.config.set_flag(&key, value.0) {
+        Ok(()) => HttpResponse::Ok().finish(),
+        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
     }
+}
+
+#[get("/flags")]
+pub async fn flags(state: Data<MState>) -> impl Responder {
+    let state = state.lock();
+    Json(state.world.config.flags())
+}
+
+#[get("/config/schema")]
+pub async fn config_schema(state: Data<MState>) -> impl Responder {
+    let state = state.lock();
+    Json(state.world.config.settings_schema())
+}
+
+#[post("/set-gradient")]
+pub async fn set_gradient(state: Data<MState>, query: Query<GradientQuery>) -> impl Responder {
+    let mut state = state.lock();
+    state
+        .world
+        .config
+        .apply_gradient(query.target, query.preset);
     HttpResponse::Ok().finish()
 }
 
@@ -139,24 +360,123 @@ pub async fn reset(state: Data<MState>) -> impl Responder {
         .inner
         .iter_mut()
         .for_each(|cell| *cell = WorldCell::Empty);
+    world
+        .field
+        .territory
+        .iter_mut()
+        .for_each(|claim| *claim = None);
     HttpResponse::Ok()
 }
 
+#[derive(Deserialize)]
+pub struct ReseedRequest {
+    seed: u64,
+    #[serde(default)]
+    green: usize,
+    #[serde(default)]
+    random: usize,
+}
+
+#[get("/seed")]
+pub async fn seed(state: Data<MState>) -> impl Responder {
+    Json(state.lock().world.get_seed())
+}
+
+#[get("/energy-flow")]
+pub async fn energy_flow(state: Data<MState>) -> impl Responder {
+    Json(state.lock().world.get_energy_flow())
+}
+
+#[get("/reproduction-stats")]
+pub async fn reproduction_stats(state: Data<MState>) -> impl Responder {
+    Json(state.lock().world.reproduction_stats())
+}
+
+#[get("/birth-heatmap")]
+pub async fn birth_heatmap(state: Data<MState>) -> impl Responder {
+    let world = &state.lock().world;
+    Json(
+        world
+            .births
+            .normalized(world.get_width(), world.get_height()),
+    )
+}
+
+#[get("/death-heatmap")]
+pub async fn death_heatmap(state: Data<MState>) -> impl Responder {
+    let world = &state.lock().world;
+    Json(
+        world
+            .deaths
+            .normalized(world.get_width(), world.get_height()),
+    )
+}
+
+#[post("/reseed")]
+pub async fn reseed(state: Data<MState>, request: Json<ReseedRequest>) -> impl Responder {
+    let mut state = state.lock();
+    let world = &mut state.world;
+    world.reseed(request.seed);
+    let green = world.populate_green(request.green);
+    let random = world.populate_random(request.random);
+    Json([green, random])
+}
+
+#[derive(Deserialize)]
+pub struct MutagenRequest {
+    i: usize,
+    j: usize,
+    radius: usize,
+    multiplier: usize,
+}
+
+#[post("/paint-mutagen")]
+pub async fn paint_mutagen(state: Data<MState>, request: Json<MutagenRequest>) -> impl Responder {
+    let mut state = state.lock();
+    let world = &mut state.world;
+    if request.i >= world.get_height() || request.j >= world.get_width() {
+        return HttpResponse::BadRequest().body("center out of bounds");
+    }
+    world.paint_mutagen((request.i, request.j), request.radius, request.multiplier);
+    HttpResponse::Ok().finish()
+}
+
+#[post("/clear-dead")]
+pub async fn clear_dead(state: Data<MState>) -> impl Responder {
+    let mut state = state.lock();
+    Json(state.world.clear_dead())
+}
+
+#[post("/disaster")]
+pub async fn disaster(state: Data<MState>, disaster: Json<Disaster>) -> impl Responder {
+    let mut state = state.lock();
+    Json(state.world.apply_disaster(*disaster))
+}
+
+#[derive(Deserialize)]
+pub struct SaveQuery {
+    #[serde(default)]
+    compact: bool,
+}
+
 #[get("/save-world")]
-pub async fn save_world(state: Data<MState>) -> impl Responder {
+pub async fn save_world(state: Data<MState>, query: Query<SaveQuery>) -> HttpResponse {
     let state = state.lock();
-    let world = &state.world;
+    let field = &state.world.field;
 
-    Json(world.field.clone())
+    if query.compact {
+        match store_field_compact(field) {
+            Ok(save) => HttpResponse::Ok().json(save),
+            Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+        }
+    } else {
+        HttpResponse::Ok().json(field)
+    }
 }
 
 const WORLD_SIZE_LIMIT: usize = 50_000_000;
 
-#[post("/load-world")]
-pub async fn load_world(
-    state: Data<MState>,
-    mut body: actix_web::web::Payload,
-) -> Result<HttpResponse, Error> {
+async fn read_payload(mut body: actix_web::web::Payload) -> Result<web::Bytes, Error> {
     let mut bytes = web::BytesMut::with_capacity(WORLD_SIZE_LIMIT);
     while let Some(item) = body.next().await {
         let item = item?;
@@ -165,14 +485,65 @@ pub async fn load_world(
         }
         bytes.extend_from_slice(&item);
     }
+    Ok(bytes.freeze())
+}
 
-    let data = serde_json::from_slice(&bytes.freeze())
-        .map_err(|_| error::ErrorBadRequest("failed to deserialize payload"))?;
+#[post("/load-world")]
+pub async fn load_world(
+    state: Data<MState>,
+    body: actix_web::web::Payload,
+) -> Result<HttpResponse, Error> {
+    let bytes = read_payload(body).await?;
+
+    let data =
+        load_field(&bytes).map_err(|_| error::ErrorBadRequest("failed to deserialize payload"))?;
     let mut state = state.lock();
     state.world.field = data;
     Ok(HttpResponse::Ok().finish())
 }
 
+/// replaces field with run-length encoded pattern, see [crate::rle::decode_rle]
+#[post("/load-rle")]
+pub async fn load_rle_pattern(state: Data<MState>, pattern: String) -> impl Responder {
+    let mut state = state.lock();
+    match load_rle(&mut state.world, &pattern) {
+        Ok(()) => HttpResponse::Ok().finish(),
+        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+/// dead bodies of the field as run-length encoded pattern
+#[get("/save-rle")]
+pub async fn save_rle_pattern(state: Data<MState>) -> impl Responder {
+    let pattern = encode_rle(&state.lock().world);
+    HttpResponse::Ok().content_type("text/plain").body(pattern)
+}
+
+#[get("/export")]
+pub async fn export_state(state: Data<MState>) -> Result<HttpResponse, Error> {
+    let data = state
+        .lock()
+        .export()
+        .map_err(error::ErrorInternalServerError)?;
+    Ok(HttpResponse::Ok()
+        .content_type("application/octet-stream")
+        .body(data))
+}
+
+#[post("/import")]
+pub async fn import_state(
+    state: Data<MState>,
+    body: actix_web::web::Payload,
+) -> Result<HttpResponse, Error> {
+    let bytes = read_payload(body).await?;
+
+    state
+        .lock()
+        .import(&bytes)
+        .map_err(|_| error::ErrorBadRequest("failed to deserialize payload"))?;
+    Ok(HttpResponse::Ok().finish())
+}
+
 #[post("/auth")]
 pub async fn auth(state: Data<MState>, password: Json<String>) -> impl Responder {
     let state = state.lock();
@@ -182,3 +553,197 @@ pub async fn auth(state: Data<MState>, password: Json<String>) -> impl Responder
         HttpResponse::Unauthorized().finish()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, web::Data, App};
+
+    use std::collections::BTreeMap;
+
+    use super::{
+        dashboard, flags, get_genome, get_registers, inspect_json, seed, set_flag, spawn_genome,
+        step_cell,
+    };
+    use crate::{
+        cells::{
+            organism::Organism,
+            world::{World, WorldCell},
+        },
+        serialization::{OrganismGenome, RegistersSnapshot},
+        state::ServerState,
+    };
+
+    fn test_state() -> Data<crate::state::MState> {
+        let mut world = World::empty::<5, 5>(Default::default());
+        world.field[(1, 2)] = WorldCell::Organism(Box::new(Organism::green(100)));
+        Data::from(Arc::new(parking_lot::Mutex::new(ServerState::new(
+            world,
+            "password".to_string(),
+            "secret".to_string(),
+        ))))
+    }
+
+    #[actix_web::test]
+    async fn test_registers_endpoint() {
+        let state = test_state();
+        let app =
+            test::init_service(App::new().app_data(state.clone()).service(get_registers)).await;
+
+        let request = test::TestRequest::get().uri("/registers/1/2").to_request();
+        let snapshot: RegistersSnapshot = test::call_and_read_body_json(&app, request).await;
+
+        match &state.lock().world.field[(1, 2)] {
+            WorldCell::Organism(bot) => {
+                assert_eq!(snapshot.registers.len(), 16);
+                assert_eq!(&snapshot.registers, bot.get_registers());
+                assert_eq!(snapshot.ip, bot.get_ip());
+            }
+            _ => unreachable!(),
+        }
+
+        for uri in ["/registers/0/0", "/registers/0/7"] {
+            let request = test::TestRequest::get().uri(uri).to_request();
+            let response = test::call_service(&app, request).await;
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_cell_endpoints_reject_column_past_width() {
+        let state = test_state();
+        let app = test::init_service(
+            App::new()
+                .app_data(state.clone())
+                .service(inspect_json)
+                .service(step_cell),
+        )
+        .await;
+
+        let request = test::TestRequest::get()
+            .uri("/inspect-json/0/7")
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let request = test::TestRequest::post().uri("/step-cell/0/7").to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let state = state.lock();
+        match &state.world.field[(1, 2)] {
+            WorldCell::Organism(bot) => assert_eq!(bot.get_energy(), 100),
+            _ => unreachable!(),
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_spawn_genome_rejects_out_of_bounds() {
+        let state = test_state();
+        let app =
+            test::init_service(App::new().app_data(state.clone()).service(spawn_genome)).await;
+        let program = *Organism::green(100).get_program();
+
+        let spawn = |i: usize, j: usize| {
+            test::TestRequest::post()
+                .uri("/spawn-genome")
+                .set_json(serde_json::json!({"i": i, "j": j, "program": program}))
+                .to_request()
+        };
+
+        // (0, 7) would wrap onto (1, 2) of 5 cells wide world
+        let response = test::call_service(&app, spawn(0, 7)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = test::call_service(&app, spawn(1, 2)).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let response = test::call_service(&app, spawn(0, 0)).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(state.lock().world.count_alive(), 2);
+    }
+
+    #[actix_web::test]
+    async fn test_genome_endpoint() {
+        let state = test_state();
+        let app = test::init_service(App::new().app_data(state.clone()).service(get_genome)).await;
+
+        let request = test::TestRequest::get().uri("/genome/1/2").to_request();
+        let genome: OrganismGenome = test::call_and_read_body_json(&app, request).await;
+        assert_eq!(genome.program, *Organism::green(100).get_program());
+        assert_eq!(genome.energy, 100);
+
+        // (0, 7) would wrap onto the organism at (1, 2) if column was not checked
+        for uri in ["/genome/0/0", "/genome/9/9", "/genome/0/7"] {
+            let request = test::TestRequest::get().uri(uri).to_request();
+            let response = test::call_service(&app, request).await;
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_set_flag_roundtrip() {
+        let state = test_state();
+        let app = test::init_service(
+            App::new()
+                .app_data(state.clone())
+                .service(set_flag)
+                .service(flags),
+        )
+        .await;
+
+        let request = test::TestRequest::post()
+            .uri("/set-flag/wrap_rows")
+            .set_json(true)
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, request).await.status(),
+            StatusCode::OK
+        );
+
+        let request = test::TestRequest::get().uri("/flags").to_request();
+        let values: BTreeMap<String, bool> = test::call_and_read_body_json(&app, request).await;
+        assert!(values["wrap_rows"]);
+        assert!(values["wrap_columns"]);
+
+        let request = test::TestRequest::post()
+            .uri("/set-flag/no_such_flag")
+            .set_json(true)
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, request).await.status(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_seed_endpoint() {
+        let state = test_state();
+        let expected = state.lock().world.get_seed();
+        let app = test::init_service(App::new().app_data(state.clone()).service(seed)).await;
+
+        let request = test::TestRequest::get().uri("/seed").to_request();
+        let reported: u64 = test::call_and_read_body_json(&app, request).await;
+        assert_eq!(reported, expected);
+    }
+
+    #[actix_web::test]
+    async fn test_dashboard_sections() {
+        let state = test_state();
+        {
+            let mut state = state.lock();
+            state.world.tick();
+            state.take_measure();
+            state.world.tick();
+        }
+        let app = test::init_service(App::new().app_data(state.clone()).service(dashboard)).await;
+
+        let request = test::TestRequest::get().uri("/dashboard").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, request).await;
+
+        let iteration = state.lock().world.get_iteration();
+        assert_eq!(body["stats"]["iteration"], iteration);
+        assert_eq!(body["world"]["cells"].as_array().unwrap().len(), 1);
+        let history = body["history"].as_array().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0]["iteration"], iteration - 1);
+    }
+}
@@ -0,0 +1,126 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cells::world::World,
+    serialization::{store_genomes, SerializedGenome},
+};
+
+pub const SNAPSHOT_DIR: &str = "bottlenecks";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BottleneckSnapshot {
+    pub iteration: usize,
+    pub population: usize,
+    pub genomes: Vec<SerializedGenome>,
+}
+
+/// watches population size and produces a snapshot once per drop below
+/// `bottleneck_threshold`. Monitor is re-armed only after population
+/// recovers above the threshold, so hovering around it does not spam snapshots
+#[derive(Clone, Debug, Default)]
+pub struct BottleneckMonitor {
+    armed: bool,
+}
+
+impl BottleneckMonitor {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn check(&mut self, world: &World) -> Option<BottleneckSnapshot> {
+        let threshold = world.config.bottleneck_threshold;
+        if threshold == 0 {
+            return None;
+        }
+
+        let population = world.count_alive();
+
+        if population > threshold {
+            self.armed = true;
+            return None;
+        }
+
+        if population < threshold && self.armed {
+            self.armed = false;
+            return Some(BottleneckSnapshot {
+                iteration: world.get_iteration(),
+                population,
+                genomes: store_genomes(world),
+            });
+        }
+
+        None
+    }
+}
+
+pub fn write_snapshot(dir: &Path, snapshot: &BottleneckSnapshot) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let path = dir.join(format!(
+        "bottleneck-{}-{}.json",
+        timestamp, snapshot.iteration
+    ));
+    fs::write(&path, serde_json::to_vec(snapshot)?)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::BottleneckMonitor;
+    use crate::cells::world::{World, WorldCell, WorldConfig};
+
+    fn kill_all_but(world: &mut World, survivors: usize) {
+        let mut left = survivors;
+        for cell in world.field.inner.iter_mut() {
+            if let WorldCell::Organism(..) = cell {
+                if left == 0 {
+                    *cell = WorldCell::Empty;
+                } else {
+                    left -= 1;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_snapshot_once_per_bottleneck() {
+        let config = WorldConfig {
+            bottleneck_threshold: 5,
+            ..Default::default()
+        };
+        let mut world = World::empty::<10, 10>(config);
+        let mut monitor = BottleneckMonitor::new();
+
+        // empty world never crossed the threshold downward
+        assert!(monitor.check(&world).is_none());
+
+        world.populate_green(10);
+        assert!(monitor.check(&world).is_none());
+
+        kill_all_but(&mut world, 3);
+        let snapshot = monitor.check(&world).expect("expected snapshot");
+        assert_eq!(snapshot.population, 3);
+        assert_eq!(snapshot.genomes.len(), 3);
+
+        assert!(monitor.check(&world).is_none());
+        kill_all_but(&mut world, 1);
+        assert!(monitor.check(&world).is_none());
+
+        world.populate_green(9);
+        assert!(monitor.check(&world).is_none());
+
+        kill_all_but(&mut world, 2);
+        assert!(monitor.check(&world).is_some());
+    }
+}
@@ -2,30 +2,54 @@ use serde::Deserialize;
 
 use crate::cells::organism::Organism;
 
-#[derive(Clone, Debug, Default)]
-pub struct ObjectCache<const N: usize> {
-    internal_buffer: heapless::Vec<Box<Organism>, N>,
+/// capacity of caches that were not sized for a particular world, eg. deserialized ones
+pub const DEFAULT_CACHE_CAPACITY: usize = 2500;
+
+#[derive(Clone, Debug)]
+pub struct ObjectCache {
+    // boxes are the point of the cache, they are handed out as ready allocations
+    #[allow(clippy::vec_box)]
+    internal_buffer: Vec<Box<Organism>>,
+    capacity: usize,
+}
+
+impl Default for ObjectCache {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_CACHE_CAPACITY)
+    }
 }
 
-impl<const N: usize> ObjectCache<N> {
-    pub fn new() -> Self {
+impl ObjectCache {
+    /// cache keeping at most `capacity` allocations, memory is reserved lazily
+    pub fn with_capacity(capacity: usize) -> Self {
         ObjectCache {
-            internal_buffer: heapless::Vec::new(),
+            internal_buffer: Vec::new(),
+            capacity,
         }
     }
 
     pub fn store_drop(&mut self, item: Box<Organism>) -> bool {
-        self.internal_buffer.push(item).is_ok()
+        if self.internal_buffer.len() < self.capacity {
+            self.internal_buffer.push(item);
+            true
+        } else {
+            false
+        }
     }
 
     pub fn get_alloc(&mut self) -> Box<Organism> {
-        self.internal_buffer
-            .pop()
-            .unwrap_or_else(|| Box::new(Default::default()))
+        self.internal_buffer.pop().unwrap_or_default()
+    }
+
+    /// estimated bytes held by the cache, including cached allocations
+    pub fn memory_footprint(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.internal_buffer.capacity() * std::mem::size_of::<Box<Organism>>()
+            + self.internal_buffer.len() * std::mem::size_of::<Organism>()
     }
 }
 
-impl<'de, const N: usize> Deserialize<'de> for ObjectCache<N> {
+impl<'de> Deserialize<'de> for ObjectCache {
     fn deserialize<D>(_deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
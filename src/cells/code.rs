@@ -1,26 +1,25 @@
 use std::fmt::Display;
+use std::hash::{Hash, Hasher};
 use std::ops::Deref;
 
 use itertools::Itertools;
 use rand::distributions::{Bernoulli, Standard};
 use rand::prelude::Distribution;
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
 use serde::{Deserialize, Serialize};
 
 pub const CODE_SIZE: usize = 256;
 
-use serde_big_array::big_array;
+use serde_big_array::BigArray;
 
-big_array! { BigOpcodeArray;}
-
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Program {
-    #[serde(with = "BigOpcodeArray")]
+    #[serde(with = "BigArray")]
     pub code: [OpCode; CODE_SIZE],
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct PackedAdressPair(u8);
 
 impl PackedAdressPair {
@@ -35,7 +34,7 @@ impl From<u8> for PackedAdressPair {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct PackedAddress(u8);
 
 impl PackedAddress {
@@ -50,7 +49,7 @@ impl From<u8> for PackedAddress {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum OpCode {
     LoadInt(u8),
     CopyRegisters(PackedAdressPair),
@@ -72,6 +71,36 @@ pub enum OpCode {
     Share,
     ShareMinerals,
     Sythesize,
+    SetArmor,
+    SetHome,
+    SenseHomeDirection,
+    CanClone,
+    ClaimTerritory,
+    SenseTerritory,
+    StoreMinerals,
+    Broadcast,
+    Receive,
+    CompareEnergy,
+    Sporulate,
+    SenseCrowdDirection,
+    SetAttackPower,
+    SelfDestruct,
+    SenseTime,
+    CloneWithMinerals,
+    RandomDirection,
+    SenseDeadDensity,
+    SenseLastPrey,
+    SenseEnergyTrend,
+    Graze,
+    SenseDimensions,
+    /// absorbs adjacent kin freeing its cell, energy above `max_cell_size`
+    /// and minerals above capacity are discarded
+    Fuse,
+    TurnLeft,
+    TurnRight,
+    SenseMineralHeadroom,
+    Glow,
+    SenseOpenness,
 }
 
 impl OpCode {
@@ -121,6 +150,34 @@ impl OpCode {
                 OpCode::UseMinerals => "use minerals".to_string(),
                 OpCode::Share => "share energy".to_string(),
                 OpCode::ShareMinerals => "share minerals".to_string(),
+                OpCode::SetArmor => "set armor".to_string(),
+                OpCode::SetHome => "set home".to_string(),
+                OpCode::SenseHomeDirection => "sense home direction".to_string(),
+                OpCode::CanClone => "can clone".to_string(),
+                OpCode::ClaimTerritory => "claim territory".to_string(),
+                OpCode::SenseTerritory => "sense territory".to_string(),
+                OpCode::StoreMinerals => "store minerals".to_string(),
+                OpCode::Broadcast => "broadcast".to_string(),
+                OpCode::Receive => "receive".to_string(),
+                OpCode::CompareEnergy => "compare energy".to_string(),
+                OpCode::Sporulate => "sporulate".to_string(),
+                OpCode::SenseCrowdDirection => "sense crowd direction".to_string(),
+                OpCode::SetAttackPower => "set attack power".to_string(),
+                OpCode::SelfDestruct => "self destruct".to_string(),
+                OpCode::SenseTime => "sense time".to_string(),
+                OpCode::CloneWithMinerals => "clone with minerals".to_string(),
+                OpCode::RandomDirection => "random direction".to_string(),
+                OpCode::SenseDeadDensity => "sense dead density".to_string(),
+                OpCode::SenseLastPrey => "sense last prey".to_string(),
+                OpCode::SenseEnergyTrend => "sense energy trend".to_string(),
+                OpCode::Graze => "graze".to_string(),
+                OpCode::SenseDimensions => "sense dimensions".to_string(),
+                OpCode::Fuse => "fuse".to_string(),
+                OpCode::TurnLeft => "turn left".to_string(),
+                OpCode::TurnRight => "turn right".to_string(),
+                OpCode::SenseMineralHeadroom => "sense mineral headroom".to_string(),
+                OpCode::Glow => "glow".to_string(),
+                OpCode::SenseOpenness => "sense openness".to_string(),
             }
         )
     }
@@ -130,7 +187,7 @@ impl Distribution<OpCode> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> OpCode {
         use OpCode::*;
         let param: u8 = rng.gen();
-        match rng.gen_range(0..=16) {
+        match rng.gen_range(0..=44) {
             0 => LoadInt(param),
             1 => CopyRegisters(param.into()),
             2 => Add(param.into()),
@@ -148,47 +205,183 @@ impl Distribution<OpCode> for Standard {
             13 => UseMinerals,
             14 => Share,
             15 => ShareMinerals,
+            16 => SetArmor,
+            17 => SetHome,
+            18 => SenseHomeDirection,
+            19 => CanClone,
+            20 => ClaimTerritory,
+            21 => SenseTerritory,
+            22 => StoreMinerals,
+            23 => Broadcast,
+            24 => Receive,
+            25 => CompareEnergy,
+            26 => Sporulate,
+            27 => SenseCrowdDirection,
+            28 => SetAttackPower,
+            29 => SelfDestruct,
+            30 => SenseTime,
+            31 => CloneWithMinerals,
+            32 => RandomDirection,
+            33 => SenseDeadDensity,
+            34 => SenseLastPrey,
+            35 => SenseEnergyTrend,
+            36 => Graze,
+            37 => SenseDimensions,
+            38 => Fuse,
+            39 => TurnLeft,
+            40 => TurnRight,
+            41 => SenseMineralHeadroom,
+            42 => Glow,
+            43 => SenseOpenness,
 
             _ => OpCode::Sythesize,
         }
     }
 }
 
+/// 64 bit FNV-1a followed by splitmix64 finalizer, so that every output bit depends
+/// on every input bit. Unlike std hashers its output is fixed, so signatures
+/// stay comparable between builds and saves
+struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        StableHasher(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+
+    // widened so that hashes do not depend on pointer size
+    fn write_usize(&mut self, value: usize) {
+        self.write(&(value as u64).to_le_bytes());
+    }
+
+    fn write_isize(&mut self, value: isize) {
+        self.write(&(value as i64).to_le_bytes());
+    }
+
+    fn finish(&self) -> u64 {
+        let mut hash = self.0;
+        hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d049bb133111eb);
+        hash ^ (hash >> 31)
+    }
+}
+
+/// relative weights of mutation kinds applied by [Program::clone_lossy]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MutationKind {
+    /// gene is replaced by a random one
+    pub substitution: usize,
+    /// random gene is inserted shifting the rest down and dropping the last one,
+    /// or gene is deleted shifting the rest up and padding the tail with synthesis
+    pub indel: usize,
+}
+
+impl Default for MutationKind {
+    fn default() -> Self {
+        MutationKind {
+            substitution: 1,
+            indel: 0,
+        }
+    }
+}
+
+/// per-gene mutation probability counted as chance/1000 together with kind weights
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Mutation {
+    pub chance: usize,
+    pub kind: MutationKind,
+}
+
 impl Program {
-    pub fn random_program() -> Self {
+    pub fn random_program<R: Rng + ?Sized>(rng: &mut R) -> Self {
         let mut items = heapless::Vec::<OpCode, CODE_SIZE>::new();
         for _ in 0..CODE_SIZE {
-            items.push(rand::random()).unwrap();
+            items.push(rng.gen()).unwrap();
         }
         Program {
             code: items.into_array().unwrap(),
         }
     }
 
-    ///probability is counted as mutation_chance/1000
-    pub fn clone_lossy(&self, mutation_chance: usize) -> Self {
-        let mut items = heapless::Vec::<OpCode, CODE_SIZE>::new();
+    ///probability is counted as mutation.chance/1000
+    pub fn clone_lossy<R: Rng + ?Sized>(&self, mutation: Mutation, rng: &mut R) -> Self {
+        let MutationKind {
+            substitution,
+            indel,
+        } = mutation.kind;
+        let mut code = self.code;
         for idx in 0..CODE_SIZE {
-            if thread_rng().gen::<usize>() % 1000usize < mutation_chance {
-                items.push(rand::random()).unwrap();
+            if rng.gen::<usize>() % 1000usize >= mutation.chance {
+                continue;
+            }
+            // kind is only drawn when indels are enabled to keep substitution-only runs stable
+            if indel == 0 || rng.gen_range(0..substitution + indel) < substitution {
+                code[idx] = rng.gen();
+            } else if rng.gen_bool(0.5) {
+                code.copy_within(idx..CODE_SIZE - 1, idx + 1);
+                code[idx] = rng.gen();
             } else {
-                items.push(self.code[idx]).unwrap();
+                code.copy_within(idx + 1.., idx);
+                code[CODE_SIZE - 1] = OpCode::Sythesize;
             }
         }
-        Program {
-            code: items.into_array().unwrap(),
+        Program { code }
+    }
+
+    /// number of genes differing between two programs
+    pub fn distance(&self, other: &Program) -> usize {
+        self.code
+            .iter()
+            .zip(other.code.iter())
+            .filter(|(a, b)| a != b)
+            .count()
+    }
+
+    /// 64 bit fingerprint where every bit is a parity of hashes of every 64th gene and its position,
+    /// programs differing in `n` genes have signatures differing in at most `n` bits
+    pub fn signature(&self) -> u64 {
+        let mut signature = 0;
+        for (idx, gene) in self.code.iter().enumerate() {
+            let mut hasher = StableHasher::default();
+            (idx, gene).hash(&mut hasher);
+            signature ^= (hasher.finish() & 1) << (idx % 64);
         }
+        signature
+    }
+
+    /// single- or two-point crossover, genes between the points are taken from `other`
+    pub fn crossover<R: Rng + ?Sized>(&self, other: &Program, rng: &mut R) -> Self {
+        let first = rng.gen_range(0..=CODE_SIZE);
+        let second = if rng.gen_bool(0.5) {
+            CODE_SIZE
+        } else {
+            rng.gen_range(0..=CODE_SIZE)
+        };
+        let (from, to) = (first.min(second), first.max(second));
+
+        let mut code = self.code;
+        code[from..to].copy_from_slice(&other.code[from..to]);
+        Program { code }
     }
 
-    pub fn break_with_chance(&mut self, damage_chance: &Bernoulli) {
-        if damage_chance.sample(&mut thread_rng()) {
-            let instruction = &mut self.code[thread_rng().gen::<usize>() % self.code.len()];
-            *instruction = thread_rng().gen();
+    pub fn break_with_chance<R: Rng + ?Sized>(&mut self, damage_chance: &Bernoulli, rng: &mut R) {
+        if damage_chance.sample(rng) {
+            let instruction = &mut self.code[rng.gen::<usize>() % self.code.len()];
+            *instruction = rng.gen();
         }
     }
 
-    pub fn print_minimized(&self, mut ip: usize) -> String {
-        let mut markers = vec![false; 256];
+    /// marks instructions reachable from `ip` following jumps and both skip branches
+    fn reachable(&self, mut ip: usize) -> Vec<bool> {
+        let mut markers = vec![false; CODE_SIZE];
         let mut resume_point = vec![0];
 
         while !markers[ip] || !resume_point.is_empty() {
@@ -210,6 +403,38 @@ impl Program {
                 }
             }
         }
+        markers
+    }
+
+    /// number of instructions reachable from the start of the program
+    pub fn complexity(&self) -> usize {
+        self.reachable(0)
+            .into_iter()
+            .filter(|&marked| marked)
+            .count()
+    }
+
+    /// instructions reachable from `ip` together with their indices
+    pub fn reachable_genes(&self, ip: usize) -> Vec<(u8, OpCode)> {
+        self.reachable(ip)
+            .into_iter()
+            .enumerate()
+            .filter(|(_, marked)| *marked)
+            .map(|(idx, _)| (idx as u8, self.code[idx]))
+            .collect()
+    }
+
+    /// program holding given genes, remaining slots are filled with no-op jumps
+    pub fn from_genes(genes: &[(u8, OpCode)]) -> Self {
+        let mut code = [OpCode::JumpUnconditional(1); CODE_SIZE];
+        for &(idx, gene) in genes {
+            code[idx as usize] = gene;
+        }
+        Program { code }
+    }
+
+    pub fn print_minimized(&self, ip: usize) -> String {
+        let markers = self.reachable(ip);
 
         self.code
             .iter()
@@ -249,15 +474,72 @@ impl Deref for Program {
 
 #[cfg(test)]
 mod test {
-    use super::Program;
+    use rand::{rngs::StdRng, thread_rng, SeedableRng};
+
+    use super::{Mutation, MutationKind, OpCode, Program, CODE_SIZE};
 
     #[test]
     fn test_program_serialization() {
-        let program = Program::random_program();
+        let program = Program::random_program(&mut thread_rng());
 
         let json = serde_json::to_string(&program).unwrap();
 
         let recovered: Program = serde_json::from_str(json.as_str()).unwrap();
         assert_eq!(program, recovered);
     }
+
+    #[test]
+    fn test_crossover_genes_come_from_parents() {
+        let mut rng = thread_rng();
+        let a = Program::random_program(&mut rng);
+        let b = Program::random_program(&mut rng);
+
+        for _ in 0..100 {
+            let child = a.crossover(&b, &mut rng);
+            for idx in 0..CODE_SIZE {
+                assert!(child[idx] == a[idx] || child[idx] == b[idx]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_signature_is_stable() {
+        // fixed value catches hashing changes that would invalidate remembered prey
+        let program = Program {
+            code: [OpCode::Sythesize; CODE_SIZE],
+        };
+        assert_eq!(program.signature(), 0xa05a71af68aaae32);
+
+        let mut mutant = program;
+        mutant.code[7] = OpCode::Eat;
+        assert!((program.signature() ^ mutant.signature()).count_ones() <= 1);
+    }
+
+    #[test]
+    fn test_indel_shifts_genes() {
+        let mut code = [OpCode::Sythesize; CODE_SIZE];
+        for (idx, gene) in code.iter_mut().enumerate() {
+            *gene = OpCode::LoadInt(idx as u8);
+        }
+        let program = Program { code };
+        let mutation = Mutation {
+            chance: 20,
+            kind: MutationKind {
+                substitution: 0,
+                indel: 1,
+            },
+        };
+
+        // fixed seed keeps the test from failing on a rare run without any mutation
+        let child = program.clone_lossy(mutation, &mut StdRng::seed_from_u64(7));
+        assert_eq!(child.code.len(), CODE_SIZE);
+        assert!(child.distance(&program) > 0);
+        // without substitutions genes differ only by being shifted by insertion or deletion
+        let shifted = (1..CODE_SIZE - 1).any(|idx| {
+            child.code[idx] != program.code[idx]
+                && (child.code[idx] == program.code[idx - 1]
+                    || child.code[idx] == program.code[idx + 1])
+        });
+        assert!(shifted);
+    }
 }
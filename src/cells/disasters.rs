@@ -0,0 +1,131 @@
+use rand::Rng;
+use serde::Deserialize;
+
+use super::world::{World, WorldCell};
+
+/// disturbance injected into the world, all randomness is drawn from world rng
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Disaster {
+    /// clears every cell within `radius` of a random point
+    Meteor { radius: usize },
+    /// adds `amount` of light to every row for `duration` ticks
+    Bloom { amount: usize, duration: usize },
+    /// kills every living organism with probability `fraction`
+    Plague { fraction: f64 },
+}
+
+impl World {
+    /// applies disaster returning number of affected cells
+    pub fn apply_disaster(&mut self, disaster: Disaster) -> usize {
+        match disaster {
+            Disaster::Meteor { radius } => self.meteor(radius),
+            Disaster::Bloom { amount, duration } => {
+                self.boost_light(amount, duration);
+                self.get_height() * self.get_width()
+            }
+            Disaster::Plague { fraction } => self.plague(fraction),
+        }
+    }
+
+    pub fn meteor(&mut self, radius: usize) -> usize {
+        let center = {
+            let mut rng = self.rng();
+            (
+                rng.gen_range(0..self.get_height()),
+                rng.gen_range(0..self.get_width()),
+            )
+        };
+
+        let mut cleared = 0;
+        for i in 0..self.get_height() {
+            for j in 0..self.get_width() {
+                let (di, dj) = self.toroidal_distance(center, (i, j));
+                if di * di + dj * dj <= radius.saturating_mul(radius)
+                    && !matches!(self.field[(i, j)], WorldCell::Empty)
+                {
+                    if let WorldCell::Organism(o) = &self.field[(i, j)] {
+                        let offspring_count = o.get_offspring_count();
+                        self.record_death((i, j), offspring_count);
+                    }
+                    self.field[(i, j)] = WorldCell::Empty;
+                    cleared += 1;
+                }
+            }
+        }
+        cleared
+    }
+
+    pub fn plague(&mut self, fraction: f64) -> usize {
+        let fraction = fraction.clamp(0.0, 1.0);
+        let dead_energy = self.config.dead_energy;
+        let width = self.get_width();
+
+        let mut killed = 0;
+        for idx in 0..self.field.inner.len() {
+            if let WorldCell::Organism(o) = &self.field.inner[idx] {
+                let (minerals, offspring_count) = (o.get_minerals(), o.get_offspring_count());
+                if self.rng().gen_bool(fraction) {
+                    self.record_death((idx / width, idx % width), offspring_count);
+                    self.field.inner[idx] =
+                        WorldCell::DeadBody(dead_energy, minerals, self.get_iteration());
+                    killed += 1;
+                }
+            }
+        }
+        killed
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Disaster;
+    use crate::cells::world::{World, WorldCell};
+
+    #[test]
+    fn test_plague_kills_about_half() {
+        let mut world = World::seeded::<100, 50>(Default::default(), 3);
+        world.populate_green(4000);
+
+        let killed = world.apply_disaster(Disaster::Plague { fraction: 0.5 });
+
+        assert_eq!(world.count_alive(), 4000 - killed);
+        assert!((1800..=2200).contains(&killed), "killed {}", killed);
+    }
+
+    #[test]
+    fn test_disaster_deaths_are_recorded() {
+        let mut world = World::seeded::<10, 10>(Default::default(), 3);
+        world.field[(0, 0)] = WorldCell::DeadBody(10, 0, 0);
+        world.populate_green(50);
+
+        let killed = world.apply_disaster(Disaster::Plague { fraction: 1.0 });
+        assert_eq!(killed, 50);
+        world.populate_green(20);
+        // meteor clears the rest, dead bodies are not counted as deaths
+        world.apply_disaster(Disaster::Meteor { radius: 100 });
+
+        let completed: usize = world.reproduction_stats().completed.values().sum();
+        assert_eq!(completed, 70);
+        let heat: f32 = (0..100).map(|idx| world.deaths.get(idx)).sum();
+        assert_eq!(heat, 70.0);
+    }
+
+    #[test]
+    fn test_huge_disasters_saturate() {
+        let mut world = World::seeded::<10, 10>(Default::default(), 3);
+        world.populate_green(100);
+
+        let cleared = world.apply_disaster(Disaster::Meteor { radius: usize::MAX });
+        assert_eq!(cleared, 100);
+
+        let light = world.get_light(0);
+        world.apply_disaster(Disaster::Bloom {
+            amount: 5,
+            duration: usize::MAX,
+        });
+        world.populate_green(100);
+        world.tick();
+        assert_eq!(world.get_light(0), light + 5);
+    }
+}
@@ -0,0 +1,58 @@
+/// decaying per-cell density of events such as births or deaths
+#[derive(Clone, Debug, Default)]
+pub struct Heatmap {
+    values: Vec<f32>,
+}
+
+impl Heatmap {
+    /// adds single event at given cell, layer grows to `len` cells if needed
+    pub fn record(&mut self, idx: usize, len: usize) {
+        if self.values.len() != len {
+            self.values.resize(len, 0.0);
+        }
+        self.values[idx] += 1.0;
+    }
+
+    /// multiplies every value by `retain_percent / 100`
+    pub fn decay(&mut self, retain_percent: usize) {
+        let factor = retain_percent.min(100) as f32 / 100.0;
+        self.values.iter_mut().for_each(|value| *value *= factor);
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    pub fn get(&self, idx: usize) -> f32 {
+        self.values.get(idx).copied().unwrap_or(0.0)
+    }
+
+    /// rows of values scaled so that the densest cell is 255
+    pub fn normalized(&self, width: usize, height: usize) -> Vec<Vec<u8>> {
+        let max = self.values.iter().copied().fold(0.0, f32::max);
+        let scale = if max > 0.0 { 255.0 / max } else { 0.0 };
+        (0..height)
+            .map(|i| {
+                (0..width)
+                    .map(|j| (self.get(i * width + j) * scale).round() as u8)
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Heatmap;
+
+    #[test]
+    fn test_normalized_heatmap() {
+        let mut heatmap = Heatmap::default();
+        assert_eq!(heatmap.normalized(2, 2), vec![vec![0, 0], vec![0, 0]]);
+
+        heatmap.record(1, 4);
+        heatmap.record(1, 4);
+        heatmap.record(2, 4);
+        assert_eq!(heatmap.normalized(2, 2), vec![vec![0, 255], vec![128, 0]]);
+    }
+}
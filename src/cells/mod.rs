@@ -1,3 +1,8 @@
 pub mod code;
+pub mod disasters;
+pub mod heatmap;
 pub mod organism;
+pub mod presets;
+pub mod scenarios;
+pub mod settings;
 pub mod world;
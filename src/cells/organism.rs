@@ -1,16 +1,16 @@
 use std::fmt::Display;
 
-use rand::{distributions::Bernoulli, thread_rng, Rng};
+use rand::{distributions::Bernoulli, seq::SliceRandom, Rng};
 use serde::{Deserialize, Serialize};
 
 use crate::cells::code::OpCode;
 
 use super::{
-    code::{Program, CODE_SIZE},
-    world::World,
+    code::{Mutation, Program, CODE_SIZE},
+    world::{World, WorldCell},
 };
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Direction {
     Up,
     Down,
@@ -20,6 +20,13 @@ pub enum Direction {
 
 #[allow(dead_code)]
 impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
     pub fn next_clockwise(&self) -> Direction {
         match self {
             Direction::Up => Direction::Right,
@@ -29,6 +36,10 @@ impl Direction {
         }
     }
 
+    pub fn next_counterclockwise(&self) -> Direction {
+        self.inverse().next_clockwise()
+    }
+
     pub fn as_shift(&self) -> (isize, isize) {
         match self {
             Direction::Up => (-1, 0),
@@ -65,14 +76,56 @@ impl From<Direction> for u8 {
     }
 }
 
+/// instructions executed per tick without adaptive budget
+const BASE_BUDGET: usize = 16;
+
+/// upper limit of `WorldConfig::actions_per_tick`
+pub const MAX_ACTIONS: usize = 4;
+
+pub type Actions = heapless::Vec<OrganismAction, MAX_ACTIONS>;
+
+/// maximal length of organism label in bytes
+pub const LABEL_SIZE: usize = 16;
+
+/// short human-readable tag of a designed strain, inherited by all descendants
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Label {
+    bytes: [u8; LABEL_SIZE],
+    len: u8,
+}
+
+impl Label {
+    /// fails if label does not fit into [LABEL_SIZE] bytes
+    pub fn new(label: &str) -> Option<Self> {
+        if label.len() > LABEL_SIZE {
+            return None;
+        }
+        let mut bytes = [0; LABEL_SIZE];
+        bytes[..label.len()].copy_from_slice(label.as_bytes());
+        Some(Label {
+            bytes,
+            len: label.len() as u8,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.bytes[..self.len as usize]).unwrap()
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum OrganismAction {
     TryMove(Direction),
     TryEat(Direction),
+    TryGraze(Direction),
+    TryFuse(Direction),
+    Glow,
     Die,
     TryClone(usize, usize, Direction),
     ShareEnergy(usize, Direction),
     ShareMinerals(usize, Direction),
+    ClaimTerritory,
+    SelfDestruct,
 }
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
@@ -84,12 +137,65 @@ pub struct Organism {
     energy: usize,
     stored_minerals: usize,
     pub can_clone: bool,
+
+    #[serde(default)]
+    armor: usize,
+    /// direction towards parent cell when organism is a part of multicellular body
+    #[serde(default)]
+    parent_link: Option<Direction>,
+    #[serde(default)]
+    last_move_direction: Option<Direction>,
+    /// number of ticks organism has lived for
+    #[serde(default)]
+    age: usize,
+    #[serde(default)]
+    home: Option<(usize, usize)>,
+    /// id shared by all descendants of a single spawned organism
+    #[serde(default)]
+    lineage: usize,
+    /// depth organism is adapted to, 0..=255 spans the whole world height,
+    /// see `WorldConfig::depth_mismatch_penalty`
+    #[serde(default)]
+    preferred_depth: u8,
+    /// number of reachable instructions of the program at birth
+    #[serde(default)]
+    complexity: usize,
+    /// value published by broadcast instruction, readable by neighbours
+    #[serde(default)]
+    message: u8,
+    /// scales both energy income and upkeep, see `Organism::metabolize`
+    #[serde(default = "neutral_metabolism")]
+    metabolism: u8,
+    /// spore does not act, age or decay until germination, see `WorldConfig::germination_light`
+    #[serde(default)]
+    dormant: bool,
+    /// attack strength bought for the current tick, see `mass_to_chance`
+    #[serde(default)]
+    attack_power: usize,
+    /// number of children split off by this organism
+    #[serde(default)]
+    offspring_count: usize,
+    /// not saved with the world
+    #[serde(skip)]
+    label: Option<Label>,
+    /// program signature of the last organism eaten, see [Program::signature]
+    #[serde(default)]
+    last_prey: Option<u64>,
+    /// ticks left until organism is able to eat again, see `WorldConfig::digestion_ticks`
+    #[serde(default)]
+    digestion: usize,
+    /// energy at the start of previous tick
+    #[serde(default)]
+    prev_energy: usize,
+    /// change of energy since previous tick: 0 - falling, 1 - flat, 2 - rising
+    #[serde(default)]
+    energy_trend: u8,
 }
 
-fn random_registers() -> [u8; 16] {
-    let mut res = [0; 16];
-    thread_rng().fill(&mut res);
-    res
+const NEUTRAL_METABOLISM: u8 = 128;
+
+fn neutral_metabolism() -> u8 {
+    NEUTRAL_METABOLISM
 }
 
 ///registers
@@ -101,17 +207,16 @@ fn random_registers() -> [u8; 16] {
 /// 5 - minerals
 /// 6 - energy
 /// 7 - attack
+/// 8 - armor
+/// 9 - blocked - set to 255 when action of previous tick was blocked by world edge (if enabled)
+/// 10 - darkness - set to 1 when last synthesis got less light than `synthesis_threshold`
+/// 11 - sight distance - distance to the cell seen by last look instruction
 impl Organism {
-    pub fn random(energy: usize) -> Self {
-        let code = Program::random_program();
-        Organism {
-            registers: [0; 16],
-            can_clone: code.code.iter().any(|gene| matches!(gene, OpCode::Clone)),
-            code,
-            energy,
-            stored_minerals: 0,
-            ip: 0,
-        }
+    pub fn random<R: Rng + ?Sized>(energy: usize, rng: &mut R) -> Self {
+        let mut bot = Self::with_program(energy, 0, Program::random_program(rng));
+        bot.preferred_depth = rng.gen();
+        bot.metabolism = rng.gen();
+        bot
     }
 
     pub fn green(energy: usize) -> Self {
@@ -121,17 +226,55 @@ impl Organism {
         Self::with_program(energy, 0, program)
     }
 
-    fn with_program(energy: usize, minerals: usize, program: Program) -> Self {
+    pub fn with_program(energy: usize, minerals: usize, program: Program) -> Self {
         Organism {
             registers: [0; 16],
-            can_clone: program.iter().any(|gene| matches!(gene, OpCode::Clone)),
+            can_clone: program
+                .iter()
+                .any(|gene| matches!(gene, OpCode::Clone | OpCode::CloneWithMinerals)),
             code: program,
             energy,
             stored_minerals: minerals,
             ip: 0,
+            armor: 0,
+            parent_link: None,
+            last_move_direction: None,
+            age: 0,
+            home: None,
+            lineage: 0,
+            preferred_depth: 0,
+            complexity: program.complexity(),
+            message: 0,
+            metabolism: NEUTRAL_METABOLISM,
+            dormant: false,
+            attack_power: 0,
+            offspring_count: 0,
+            label: None,
+            last_prey: None,
+            digestion: 0,
+            prev_energy: energy,
+            energy_trend: 1,
+        }
+    }
+
+    /// number of instructions executed per tick, with `adaptive_budget`
+    /// every 4 reachable instructions grant one, up to `max_instruction_budget`
+    fn instruction_budget(&self, world: &World) -> usize {
+        if world.config.adaptive_budget {
+            (self.complexity / 4).clamp(
+                BASE_BUDGET,
+                world.config.max_instruction_budget.max(BASE_BUDGET),
+            )
+        } else {
+            BASE_BUDGET
         }
     }
 
+    /// energy given to a child by clone instruction
+    fn child_energy(&self, world: &World) -> usize {
+        usize::max(world.config.start_energy, self.energy / 2)
+    }
+
     #[inline(always)]
     fn next_instruction(&mut self) {
         self.ip = (self.ip + 1) % self.code.len();
@@ -142,151 +285,458 @@ impl Organism {
         self.ip = (self.ip + delta) % self.code.len();
     }
 
+    /// tick limited to a single action
+    #[cfg(test)]
+    pub fn tick(&mut self, world: &World, pos: (usize, usize)) -> Option<OrganismAction> {
+        self.tick_actions(world, pos, 1, &mut *world.rng())
+            .into_iter()
+            .next()
+    }
+
+    /// runs instructions until `max_actions` (at most [MAX_ACTIONS]) acting instructions
+    /// are executed or instruction budget is exhausted, returned actions are applied in order,
+    /// randomness is drawn from `rng` rather than world so that organisms may be run in parallel
     #[inline(always)]
-    pub fn tick(&mut self, world: &World, (i, j): (usize, usize)) -> Option<OrganismAction> {
-        self.registers[3] = thread_rng().gen();
+    pub fn tick_actions<R: Rng + ?Sized>(
+        &mut self,
+        world: &World,
+        (i, j): (usize, usize),
+        max_actions: usize,
+        rng: &mut R,
+    ) -> Actions {
+        self.registers[3] = rng.gen();
         self.registers[4] = into_u8_fraction(i, world.get_height());
-        self.registers[5] = into_u8_fraction(self.get_minerals(), world.config.max_minerals);
+        self.registers[5] = into_u8_fraction(
+            self.get_minerals(),
+            world.config.mineral_capacity(self.energy).max(1),
+        );
         self.registers[6] = into_u8_fraction(self.get_energy(), world.config.max_cell_size);
+        self.registers[8] = self.armor as u8;
+        self.attack_power = 0;
+        self.energy_trend = match self.energy.cmp(&self.prev_energy) {
+            std::cmp::Ordering::Less => 0,
+            std::cmp::Ordering::Equal => 1,
+            std::cmp::Ordering::Greater => 2,
+        };
+        self.prev_energy = self.energy;
 
+        let mut actions = Actions::new();
         if self.energy == 0 {
-            return Some(OrganismAction::Die);
+            actions.push(OrganismAction::Die).unwrap();
+            return actions;
         }
 
-        for _ in 0..16 {
-            match self.code[self.ip] {
-                OpCode::LoadInt(n) => {
-                    self.next_instruction();
-                    *self.result_register() = n;
-                }
-                OpCode::CopyRegisters(params) => {
-                    self.next_instruction();
-                    let (from, to) = params.unwrap();
-                    self.registers[to] = self.registers[from];
-                }
-                OpCode::MoveRelative => {
-                    self.next_instruction();
-                    return Some(OrganismAction::TryMove(self.get_direction()));
+        // actions are applied only after the tick, so neighbourhood is looked up
+        // once and shared by all sensing instructions of this tick
+        let neighbours = [
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+        ]
+        .map(|direction| world.look_relative((i, j), direction));
+
+        let mut performed = 0;
+        for _ in 0..self.instruction_budget(world) {
+            if let Some(action) = self.execute(world, (i, j), &neighbours, rng) {
+                if let Some(action) = action {
+                    // there are never more actions than performed acting instructions
+                    actions.push(action).unwrap();
                 }
-                OpCode::LookRelative => {
-                    self.next_instruction();
-                    let direction = self.get_direction();
-                    let world_cell = world.look_relative((i, j), direction);
-                    *self.result_register() = match world_cell {
-                        Some(super::world::WorldCell::Empty) => 0,
-
-                        Some(super::world::WorldCell::Organism(o)) => {
-                            *self.result2_register() =
-                                into_u8_fraction(o.get_energy(), world.config.max_cell_size);
-                            1
-                        }
-                        Some(super::world::WorldCell::DeadBody(..)) => 2,
-                        None => 255,
-                    };
+                performed += 1;
+                if performed == max_actions.clamp(1, MAX_ACTIONS) {
+                    break;
                 }
-                OpCode::Eat => {
-                    self.next_instruction();
-                    return Some(OrganismAction::TryEat(self.get_direction()));
+            }
+        }
+        // program has seen feedback of the previous tick, only actions
+        // of this one may set it again
+        self.registers[9] = 0;
+        actions
+    }
+
+    /// executes instruction under instruction pointer, returns None for observing
+    /// instructions and action produced by acting ones otherwise
+    #[inline(always)]
+    fn execute<R: Rng + ?Sized>(
+        &mut self,
+        world: &World,
+        (i, j): (usize, usize),
+        neighbours: &[Option<&WorldCell>; 4],
+        rng: &mut R,
+    ) -> Option<Option<OrganismAction>> {
+        match self.code[self.ip] {
+            OpCode::LoadInt(n) => {
+                self.next_instruction();
+                *self.result_register() = n;
+            }
+            OpCode::CopyRegisters(params) => {
+                self.next_instruction();
+                let (from, to) = params.unwrap();
+                self.registers[to] = self.registers[from];
+            }
+            OpCode::MoveRelative => {
+                self.next_instruction();
+                let direction = self.move_direction(world.config.movement_inertia);
+                return Some(Some(OrganismAction::TryMove(direction)));
+            }
+            OpCode::LookRelative => {
+                self.next_instruction();
+                self.decrease_energy(world.config.sense_cost);
+                let direction = self.get_direction();
+                let (world_cell, distance) = if world.config.sight_range > 1 {
+                    world.look_far((i, j), direction, world.config.sight_range)
+                } else {
+                    (neighbours[u8::from(direction) as usize], 1)
+                };
+                if let Some(WorldCell::Organism(o)) = world_cell {
+                    *self.result2_register() =
+                        into_u8_fraction(o.get_energy(), world.config.max_cell_size);
                 }
-                OpCode::Sythesize => {
-                    self.next_instruction();
-                    let generated = world.get_light(i);
+                *self.result_register() = cell_code(world_cell);
+                self.registers[11] = distance.min(255) as u8;
+            }
+            OpCode::SelfDestruct => {
+                self.next_instruction();
+                return Some(Some(OrganismAction::SelfDestruct));
+            }
+            OpCode::Eat => {
+                self.next_instruction();
+                return Some(Some(OrganismAction::TryEat(self.get_direction())));
+            }
+            OpCode::Graze => {
+                self.next_instruction();
+                return Some(Some(OrganismAction::TryGraze(self.get_direction())));
+            }
+            OpCode::Fuse => {
+                self.next_instruction();
+                return Some(Some(OrganismAction::TryFuse(self.get_direction())));
+            }
+            OpCode::Glow => {
+                self.next_instruction();
+                return Some(Some(OrganismAction::Glow));
+            }
+            OpCode::Sythesize => {
+                self.next_instruction();
+                let light = world.effective_light((i, j));
+                let threshold = world.config.synthesis_threshold;
+                let too_dark = light < threshold;
+                // dedicated register, so that synthesis between computations does not clobber them
+                self.registers[10] = too_dark as u8;
+                if !too_dark {
+                    let generated = self.metabolize(light);
                     self.add_energy(generated);
-                    return None;
+                    world.track_energy(|flow| flow.synthesized += generated);
                 }
+                return Some(None);
+            }
 
-                OpCode::Add(addr) => {
-                    self.next_instruction();
-                    let (from, to) = addr.unwrap();
-                    self.registers[from] = self.registers[from].wrapping_add(self.registers[to]);
-                }
-                OpCode::AddClip(addr) => {
-                    self.next_instruction();
-                    let (from, to) = addr.unwrap();
-                    self.registers[from] = self.registers[from].saturating_add(self.registers[to]);
-                }
-                OpCode::SubClip(addr) => {
+            OpCode::Add(addr) => {
+                self.next_instruction();
+                let (from, to) = addr.unwrap();
+                self.registers[from] = self.registers[from].wrapping_add(self.registers[to]);
+            }
+            OpCode::AddClip(addr) => {
+                self.next_instruction();
+                let (from, to) = addr.unwrap();
+                self.registers[from] = self.registers[from].saturating_add(self.registers[to]);
+            }
+            OpCode::SubClip(addr) => {
+                self.next_instruction();
+                let (from, to) = addr.unwrap();
+                self.registers[from] = self.registers[from].saturating_sub(self.registers[to]);
+            }
+            OpCode::Flip(addr) => {
+                self.next_instruction();
+                let addr = addr.unwrap();
+                self.registers[addr] = u8::from(self.registers[addr] != 0);
+            }
+            OpCode::JumpUnconditional(shift) => {
+                self.jump(shift as usize);
+            }
+            OpCode::SkipZero(addr) => {
+                if self.registers[addr.unwrap()] == 0 {
+                    self.jump(2);
+                } else {
                     self.next_instruction();
-                    let (from, to) = addr.unwrap();
-                    self.registers[from] = self.registers[from].saturating_sub(self.registers[to]);
                 }
-                OpCode::Flip(addr) => {
-                    self.next_instruction();
-                    let addr = addr.unwrap();
-                    self.registers[addr] = u8::from(self.registers[addr] != 0);
-                }
-                OpCode::JumpUnconditional(shift) => {
-                    self.jump(shift as usize);
-                }
-                OpCode::SkipZero(addr) => {
-                    if self.registers[addr.unwrap()] == 0 {
-                        self.jump(2);
-                    } else {
-                        self.next_instruction();
+            }
+            OpCode::Clone => {
+                self.next_instruction();
+                let child_energy = self.child_energy(world);
+
+                let child_minerals = self.stored_minerals / 2;
+
+                return Some(Some(OrganismAction::TryClone(
+                    child_energy,
+                    child_minerals,
+                    self.get_direction(),
+                )));
+            }
+            OpCode::CloneWithMinerals => {
+                // register 0 sets share of energy (up to a half), register 1 - share of minerals
+                self.next_instruction();
+                let child_energy = usize::max(
+                    world.config.start_energy,
+                    self.energy * self.registers[0] as usize / 512,
+                );
+                let child_minerals = self.stored_minerals * self.registers[1] as usize / 255;
+
+                return Some(Some(OrganismAction::TryClone(
+                    child_energy,
+                    child_minerals,
+                    self.get_direction(),
+                )));
+            }
+            OpCode::Compare => {
+                self.next_instruction();
+                self.decrease_energy(world.config.sense_cost);
+                let world_cell = neighbours[u8::from(self.get_direction()) as usize];
+                *self.result_register() = match world_cell {
+                    Some(super::world::WorldCell::Organism(other)) => {
+                        *self.result2_register() =
+                            into_u8_fraction(other.get_energy(), world.config.max_cell_size);
+
+                        self.code
+                            .iter()
+                            .zip(other.code.iter())
+                            .filter(|(a, b)| a != b)
+                            .count()
+                            .max(255) as u8
                     }
-                }
-                OpCode::Clone => {
-                    self.next_instruction();
-                    let child_energy = usize::max(world.config.start_energy, self.energy / 2);
+                    Some(super::world::WorldCell::DeadBody(..)) => 255,
+                    _ => 0,
+                };
+            }
 
-                    let child_minerals = self.stored_minerals / 2;
+            OpCode::UseMinerals => {
+                self.next_instruction();
+                let mineral_energy = (*self.result_register() as usize).min(self.stored_minerals);
+                self.add_energy(mineral_energy);
+                world.track_energy(|flow| flow.minerals += mineral_energy);
+                self.stored_minerals -= mineral_energy;
+                return Some(None);
+            }
+            OpCode::StoreMinerals => {
+                self.next_instruction();
+                let efficiency = world.config.mineral_storage_efficiency;
+                let capacity = world.config.mineral_capacity(self.energy);
+                let room = capacity.saturating_sub(self.stored_minerals);
+                let spent = (*self.result_register() as usize)
+                    .min(self.energy)
+                    .min((room * 100).checked_div(efficiency).unwrap_or(0));
+                self.energy -= spent;
+                self.add_minerals(spent * efficiency / 100, capacity);
+                return Some(None);
+            }
+            OpCode::Share => {
+                self.next_instruction();
+                let share_value = usize::min(*self.result_register() as usize, self.energy);
+                self.energy -= share_value;
+                return Some(Some(OrganismAction::ShareEnergy(
+                    share_value,
+                    self.get_direction(),
+                )));
+            }
 
-                    return Some(OrganismAction::TryClone(
-                        child_energy,
-                        child_minerals,
-                        self.get_direction(),
-                    ));
-                }
-                OpCode::Compare => {
-                    self.next_instruction();
-                    let direction = self.get_direction();
-                    let world_cell = world.look_relative((i, j), direction);
-                    *self.result_register() = match world_cell {
-                        Some(super::world::WorldCell::Organism(other)) => {
-                            *self.result2_register() =
-                                into_u8_fraction(other.get_energy(), world.config.max_cell_size);
-
-                            self.code
-                                .iter()
-                                .zip(other.code.iter())
-                                .filter(|(a, b)| a != b)
-                                .count()
-                                .max(255) as u8
+            OpCode::SetArmor => {
+                self.next_instruction();
+                let armor = *self.result_register() as usize;
+                self.decrease_energy(armor * world.config.armor_cost / 255);
+                self.armor = armor;
+                return Some(None);
+            }
+
+            OpCode::SenseTime => {
+                self.next_instruction();
+                self.decrease_energy(world.config.sense_cost);
+                *self.result_register() = match world.day_phase() {
+                    Some(phase) => into_u8_fraction(phase, world.config.day_length),
+                    None => world.get_iteration() as u8,
+                };
+            }
+
+            OpCode::SetAttackPower => {
+                self.next_instruction();
+                let power = *self.result_register() as usize;
+                self.decrease_energy(power * world.config.attack_power_cost / 255);
+                self.attack_power = power;
+            }
+
+            OpCode::CanClone => {
+                self.next_instruction();
+                self.decrease_energy(world.config.sense_cost);
+                let has_space = neighbours
+                    .iter()
+                    .any(|cell| matches!(cell, Some(WorldCell::Empty)));
+                let affordable = self.energy >= world.config.min_clone_parent_energy
+                    && self.can_afford_child(self.child_energy(world));
+                *self.result_register() = (has_space && affordable) as u8;
+            }
+
+            OpCode::ClaimTerritory => {
+                self.next_instruction();
+                return Some(Some(OrganismAction::ClaimTerritory));
+            }
+
+            OpCode::SenseTerritory => {
+                self.next_instruction();
+                self.decrease_energy(world.config.sense_cost);
+                *self.result_register() = match world.relative_shift((i, j), self.get_direction()) {
+                    Some(pos) => match world.territory_owner(pos) {
+                        None => 0,
+                        Some(lineage) if lineage == self.lineage => 1,
+                        Some(_) => 2,
+                    },
+                    None => 255,
+                };
+            }
+
+            OpCode::CompareEnergy => {
+                self.next_instruction();
+                self.decrease_energy(world.config.sense_cost);
+                *self.result_register() = match neighbours[u8::from(self.get_direction()) as usize]
+                {
+                    Some(WorldCell::Organism(other)) => {
+                        match compare_energy(other.get_energy(), self.energy) {
+                            std::cmp::Ordering::Less => 0,
+                            std::cmp::Ordering::Equal => 1,
+                            std::cmp::Ordering::Greater => 2,
                         }
-                        Some(super::world::WorldCell::DeadBody(..)) => 255,
-                        _ => 0,
-                    };
-                }
+                    }
+                    _ => 255,
+                };
+            }
 
-                OpCode::UseMinerals => {
-                    self.next_instruction();
-                    let mineral_energy =
-                        (*self.result_register() as usize).min(self.stored_minerals);
-                    self.add_energy(mineral_energy);
-                    self.stored_minerals -= mineral_energy;
-                    return None;
-                }
-                OpCode::Share => {
-                    self.next_instruction();
-                    let share_value = usize::min(*self.result_register() as usize, self.energy);
-                    self.energy -= share_value;
-                    return Some(OrganismAction::ShareEnergy(
-                        share_value,
-                        self.get_direction(),
-                    ));
-                }
+            OpCode::SenseCrowdDirection => {
+                self.next_instruction();
+                self.decrease_energy(world.config.sense_cost);
+                *self.result_register() = world
+                    .crowd_direction((i, j), world.config.sight_range.max(1))
+                    .map(u8::from)
+                    .unwrap_or(255);
+            }
 
-                OpCode::ShareMinerals => {
-                    self.next_instruction();
-                    let share_value =
-                        usize::min(*self.result_register() as usize, self.stored_minerals);
-                    self.stored_minerals -= share_value;
-                    return Some(OrganismAction::ShareMinerals(
-                        share_value,
-                        self.get_direction(),
-                    ));
-                }
-            };
+            OpCode::SenseMineralHeadroom => {
+                self.next_instruction();
+                self.decrease_energy(world.config.sense_cost);
+                let max_minerals = world.config.max_minerals;
+                *self.result_register() = into_u8_fraction(
+                    max_minerals.saturating_sub(self.stored_minerals),
+                    max_minerals.max(1),
+                );
+            }
+
+            OpCode::SenseOpenness => {
+                self.next_instruction();
+                self.decrease_energy(world.config.sense_cost);
+                let openness = world.openness(
+                    (i, j),
+                    self.get_direction(),
+                    world.config.sight_range.max(1),
+                );
+                *self.result_register() = openness.min(u8::MAX as usize) as u8;
+            }
+
+            OpCode::TurnLeft => {
+                self.next_instruction();
+                self.registers[2] = self.get_direction().next_counterclockwise().into();
+            }
+
+            OpCode::TurnRight => {
+                self.next_instruction();
+                self.registers[2] = self.get_direction().next_clockwise().into();
+            }
+
+            OpCode::SenseDimensions => {
+                self.next_instruction();
+                self.decrease_energy(world.config.sense_cost);
+                self.registers[0] = world.get_width().min(u8::MAX as usize) as u8;
+                self.registers[1] = world.get_height().min(u8::MAX as usize) as u8;
+            }
+
+            OpCode::SenseEnergyTrend => {
+                self.next_instruction();
+                self.decrease_energy(world.config.sense_cost);
+                *self.result_register() = self.energy_trend;
+            }
+
+            OpCode::SenseLastPrey => {
+                self.next_instruction();
+                self.decrease_energy(world.config.sense_cost);
+                let threshold = world.config.prey_similarity_threshold;
+                *self.result_register() = match neighbours[u8::from(self.get_direction()) as usize]
+                {
+                    Some(WorldCell::Organism(other)) => {
+                        self.resembles_last_prey(other, threshold) as u8
+                    }
+                    _ => 255,
+                };
+            }
+
+            OpCode::SenseDeadDensity => {
+                self.next_instruction();
+                self.decrease_energy(world.config.sense_cost);
+                *self.result_register() = neighbours
+                    .iter()
+                    .filter(|cell| matches!(cell, Some(WorldCell::DeadBody(..))))
+                    .count() as u8;
+            }
+
+            OpCode::RandomDirection => {
+                self.next_instruction();
+                self.registers[2] = (*Direction::ALL.choose(rng).unwrap()).into();
+            }
+
+            OpCode::Sporulate => {
+                self.next_instruction();
+                self.dormant = true;
+                return Some(None);
+            }
+
+            OpCode::Broadcast => {
+                self.next_instruction();
+                self.message = *self.result_register();
+            }
+
+            OpCode::Receive => {
+                self.next_instruction();
+                self.decrease_energy(world.config.sense_cost);
+                *self.result_register() = match neighbours[u8::from(self.get_direction()) as usize]
+                {
+                    Some(WorldCell::Organism(other)) => other.message,
+                    _ => 0,
+                };
+            }
+
+            OpCode::SetHome => {
+                self.next_instruction();
+                self.home = Some((i, j));
+            }
+
+            OpCode::SenseHomeDirection => {
+                self.next_instruction();
+                self.decrease_energy(world.config.sense_cost);
+                *self.result_register() = self
+                    .home
+                    .and_then(|home| world.direction_toward((i, j), home))
+                    .map_or(255, u8::from);
+                *self.result2_register() = self.home.map_or(255, |home| {
+                    let (di, dj) = world.toroidal_distance((i, j), home);
+                    (di + dj).min(255) as u8
+                });
+            }
+
+            OpCode::ShareMinerals => {
+                self.next_instruction();
+                let share_value =
+                    usize::min(*self.result_register() as usize, self.stored_minerals);
+                self.stored_minerals -= share_value;
+                return Some(Some(OrganismAction::ShareMinerals(
+                    share_value,
+                    self.get_direction(),
+                )));
+            }
         }
         None
     }
@@ -303,11 +753,31 @@ impl Organism {
         self.stored_minerals = (self.stored_minerals + minerals).min(limit);
     }
 
+    pub fn decrease_minerals(&mut self, minerals: usize) {
+        self.stored_minerals = self.stored_minerals.saturating_sub(minerals);
+    }
+
     #[inline(always)]
     fn get_direction(&self) -> Direction {
         self.registers[2].into()
     }
 
+    /// with inertia organism can not reverse its last move in a single move,
+    /// attempt to do so results in a clockwise turn instead
+    fn move_direction(&self, inertia: bool) -> Direction {
+        let wanted = self.get_direction();
+        match self.last_move_direction {
+            Some(last) if inertia && wanted == last.inverse() => last.next_clockwise(),
+            _ => wanted,
+        }
+    }
+
+    /// remembers heading of a move that has actually happened,
+    /// blocked attempts do not turn organism around
+    pub fn record_move(&mut self, direction: Direction) {
+        self.last_move_direction = Some(direction);
+    }
+
     #[inline(always)]
     fn result_register(&mut self) -> &mut u8 {
         &mut self.registers[0]
@@ -326,35 +796,207 @@ impl Organism {
         self.stored_minerals
     }
 
+    pub fn get_ip(&self) -> usize {
+        self.ip
+    }
+
+    pub fn get_program(&self) -> &Program {
+        &self.code
+    }
+
+    pub fn get_age(&self) -> usize {
+        self.age
+    }
+
+    pub fn get_armor(&self) -> usize {
+        self.armor
+    }
+
+    pub fn decay_armor(&mut self, decay: usize) {
+        self.armor = self.armor.saturating_sub(decay);
+    }
+
+    pub fn is_digesting(&self) -> bool {
+        self.digestion > 0
+    }
+
+    pub fn start_digestion(&mut self, ticks: usize) {
+        self.digestion = ticks;
+    }
+
+    pub fn digest(&mut self) {
+        self.digestion = self.digestion.saturating_sub(1);
+    }
+
+    pub fn get_home(&self) -> Option<(usize, usize)> {
+        self.home
+    }
+
+    pub fn set_home(&mut self, home: Option<(usize, usize)>) {
+        self.home = home;
+    }
+
+    pub fn get_preferred_depth(&self) -> u8 {
+        self.preferred_depth
+    }
+
+    #[cfg(test)]
+    pub fn set_preferred_depth(&mut self, depth: u8) {
+        self.preferred_depth = depth;
+    }
+
+    /// scales energy amount by metabolism gene, from 50% at 0
+    /// through 100% at neutral value to 150% at 255
+    pub fn metabolize(&self, amount: usize) -> usize {
+        amount * (50 + self.metabolism as usize * 100 / 255) / 100
+    }
+
+    #[cfg(test)]
+    pub fn set_metabolism(&mut self, metabolism: u8) {
+        self.metabolism = metabolism;
+    }
+
+    pub fn is_dormant(&self) -> bool {
+        self.dormant
+    }
+
+    pub fn germinate(&mut self) {
+        self.dormant = false;
+    }
+
+    pub fn get_complexity(&self) -> usize {
+        self.complexity
+    }
+
+    pub fn get_attack_power(&self) -> usize {
+        self.attack_power
+    }
+
+    pub fn get_label(&self) -> Option<Label> {
+        self.label
+    }
+
+    pub fn set_label(&mut self, label: Option<Label>) {
+        self.label = label;
+    }
+
+    pub fn remember_prey(&mut self, prey: &Organism) {
+        self.last_prey = Some(prey.code.signature());
+    }
+
+    /// whether program of other organism is within `threshold` signature bits of last prey
+    pub fn resembles_last_prey(&self, other: &Organism, threshold: usize) -> bool {
+        self.last_prey.is_some_and(|signature| {
+            (signature ^ other.code.signature()).count_ones() as usize <= threshold
+        })
+    }
+
+    pub fn get_offspring_count(&self) -> usize {
+        self.offspring_count
+    }
+
+    pub fn get_lineage(&self) -> usize {
+        self.lineage
+    }
+
+    pub fn set_lineage(&mut self, lineage: usize) {
+        self.lineage = lineage;
+    }
+
+    /// organisms of the same lineage are kin, as well as organisms with programs
+    /// differing in at most `genetic_threshold` genes (when threshold is not 0).
+    /// Lineage 0 belongs to organisms placed without a founder, eg. loaded from
+    /// saves predating lineages, those are never kin by lineage
+    pub fn is_kin(&self, other: &Organism, genetic_threshold: usize) -> bool {
+        (self.lineage != 0 && self.lineage == other.lineage)
+            || (genetic_threshold > 0 && self.code.distance(&other.code) <= genetic_threshold)
+    }
+
+    pub fn get_parent_link(&self) -> Option<Direction> {
+        self.parent_link
+    }
+
+    pub fn set_parent_link(&mut self, link: Option<Direction>) {
+        self.parent_link = link;
+    }
+
+    /// keeps energy, minerals and attack neighbours gave to or took from organism
+    /// since `before` was copied from it, used when `self` is a copy of `before`
+    /// that has run its program in the meantime. Actions changing anything else
+    /// in other organisms must be carried here as well
+    pub fn carry_external_changes(&mut self, before: &Organism, current: &Organism) {
+        self.energy = (self.energy + current.energy).saturating_sub(before.energy);
+        self.stored_minerals =
+            (self.stored_minerals + current.stored_minerals).saturating_sub(before.stored_minerals);
+        if current.registers[7] != before.registers[7] {
+            self.registers[7] = current.registers[7];
+        }
+    }
+
     pub fn register_attack(&mut self, direction: Direction) {
         self.registers[7] = direction.into();
     }
 
-    pub fn split_off<F: FnOnce() -> Box<Organism>>(
+    pub fn register_blocked(&mut self) {
+        self.registers[9] = 255;
+    }
+
+    pub fn get_registers(&self) -> &[u8; 16] {
+        &self.registers
+    }
+
+    /// parent must retain at least as much energy as it gives to the child
+    pub fn can_afford_child(&self, child_energy: usize) -> bool {
+        self.energy
+            .checked_sub(child_energy)
+            .is_some_and(|retained| retained >= child_energy)
+    }
+
+    pub fn split_off<F: FnOnce() -> Box<Organism>, R: Rng + ?Sized>(
         &mut self,
         allocation: F,
         energy: usize,
         minerals: usize,
-        mutation_chance: usize,
+        mutation: Mutation,
+        partner: Option<&Program>,
+        rng: &mut R,
     ) -> Option<Box<Organism>> {
-        if self.energy >= energy * 2 {
+        if self.can_afford_child(energy) {
             let mut alloc = allocation();
 
-            let child_program = self.code.clone_lossy(mutation_chance);
-            let bot = Self::with_program(energy, minerals, child_program);
+            let child_program = match partner {
+                Some(partner) => self.code.crossover(partner, rng).clone_lossy(mutation, rng),
+                None => self.code.clone_lossy(mutation, rng),
+            };
+            let mutation_chance = mutation.chance;
+            let mut bot = Self::with_program(energy, minerals, child_program);
+            bot.lineage = self.lineage;
+            bot.label = self.label;
+            bot.preferred_depth = if rng.gen::<usize>() % 1000usize < mutation_chance {
+                rng.gen()
+            } else {
+                self.preferred_depth
+            };
+            bot.metabolism = if rng.gen::<usize>() % 1000usize < mutation_chance {
+                rng.gen()
+            } else {
+                self.metabolism
+            };
 
             *alloc.as_mut() = bot;
 
             self.energy -= energy;
             self.stored_minerals -= minerals;
+            self.offspring_count += 1;
             Some(alloc)
         } else {
             None
         }
     }
 
-    pub fn age(&mut self, aging_mutation_chance: &Bernoulli) {
-        self.code.break_with_chance(aging_mutation_chance);
+    pub fn age<R: Rng + ?Sized>(&mut self, aging_mutation_chance: &Bernoulli, rng: &mut R) {
+        self.age += 1;
+        self.code.break_with_chance(aging_mutation_chance, rng);
     }
 }
 
@@ -364,6 +1006,10 @@ impl Display for Organism {
             f,
             "
 energy: {}
+minerals: {}
+can clone: {}
+age: {}
+armor: {}
 registers: {}
 ip: {}
 
@@ -375,6 +1021,10 @@ full program:
 {}
 ",
             self.get_energy(),
+            self.get_minerals(),
+            self.can_clone,
+            self.age,
+            self.armor,
             self.registers
                 .iter()
                 .map(|reg| { format!("{}", reg) })
@@ -393,7 +1043,673 @@ impl Default for Organism {
     }
 }
 
+/// code of cell as seen by look instructions
+#[inline(always)]
+fn cell_code(cell: Option<&WorldCell>) -> u8 {
+    match cell {
+        Some(WorldCell::Empty) => 0,
+        Some(WorldCell::Organism(..)) => 1,
+        Some(WorldCell::DeadBody(..)) => 2,
+        None => 255,
+    }
+}
+
+/// energies differing by at most this percentage of the larger one are considered equal
+const ENERGY_TOLERANCE_PERCENT: usize = 5;
+
+/// compares energies of two organisms treating close values as equal
+fn compare_energy(energy: usize, other: usize) -> std::cmp::Ordering {
+    if energy.abs_diff(other) * 100 <= energy.max(other) * ENERGY_TOLERANCE_PERCENT {
+        std::cmp::Ordering::Equal
+    } else {
+        energy.cmp(&other)
+    }
+}
+
 #[inline(always)]
 fn into_u8_fraction(value: usize, divisor: usize) -> u8 {
     ((value * 255usize) / divisor).clamp(0, 255) as u8
 }
+
+#[cfg(test)]
+mod test {
+    use rand::{distributions::Bernoulli, thread_rng};
+
+    use super::{into_u8_fraction, Direction, Label, Organism, OrganismAction};
+    use crate::cells::{
+        code::{Mutation, OpCode, Program, CODE_SIZE},
+        world::{World, WorldCell, WorldConfig},
+    };
+
+    #[test]
+    fn test_look_spamming_takes_no_action() {
+        let mut world = World::empty::<10, 10>(Default::default());
+        world.field[(4, 5)] = WorldCell::Organism(Box::new(Organism::green(100)));
+
+        let mut bot = Organism::with_program(
+            100,
+            0,
+            Program {
+                code: [OpCode::LookRelative; CODE_SIZE],
+            },
+        );
+
+        for _ in 0..100 {
+            bot.registers[2] = u8::from(Direction::Up);
+            assert!(bot.tick(&world, (5, 5)).is_none());
+            assert_eq!(bot.registers[0], 1);
+        }
+    }
+
+    #[test]
+    fn test_sensing_costs_energy() {
+        let world = World::empty::<10, 10>(WorldConfig {
+            sense_cost: 2,
+            ..Default::default()
+        });
+
+        let mut code = [OpCode::Sythesize; CODE_SIZE];
+        for idx in (0..CODE_SIZE).step_by(2) {
+            code[idx] = OpCode::LookRelative;
+        }
+        let mut sensing = Organism::with_program(100, 0, Program { code });
+        let mut plain = Organism::green(100);
+
+        for _ in 0..20 {
+            sensing.tick(&world, (0, 0));
+            plain.tick(&world, (0, 0));
+        }
+
+        assert_eq!(plain.get_energy() - sensing.get_energy(), 20 * 2);
+    }
+
+    #[test]
+    fn test_movement_inertia() {
+        for inertia in [false, true] {
+            let mut world = World::empty::<10, 10>(WorldConfig {
+                movement_inertia: inertia,
+                aging_mutation_freq: Bernoulli::new(0.0).unwrap(),
+                ..Default::default()
+            });
+            let mut bot = Organism::with_program(
+                100,
+                0,
+                Program {
+                    code: [OpCode::MoveRelative; CODE_SIZE],
+                },
+            );
+            bot.registers[2] = u8::from(Direction::Right);
+            world.field[(5, 5)] = WorldCell::Organism(Box::new(bot));
+
+            world.tick();
+            world.tick();
+            match &mut world.field[(5, 7)] {
+                WorldCell::Organism(bot) => bot.registers[2] = u8::from(Direction::Left),
+                _ => panic!("organism has not moved right"),
+            }
+            world.tick();
+
+            let destination = if inertia { (6, 7) } else { (5, 6) };
+            assert!(matches!(world.field[destination], WorldCell::Organism(..)));
+            assert_eq!(world.count_alive(), 1);
+        }
+    }
+
+    #[test]
+    fn test_organisms_without_lineage_are_not_kin() {
+        let (mut a, mut b) = (Organism::green(10), Organism::green(10));
+        assert!(!a.is_kin(&b, 0));
+
+        a.set_lineage(3);
+        b.set_lineage(3);
+        assert!(a.is_kin(&b, 0));
+    }
+
+    #[test]
+    fn test_blocked_move_keeps_heading() {
+        let mut world = World::empty::<10, 10>(WorldConfig {
+            movement_inertia: true,
+            aging_mutation_freq: Bernoulli::new(0.0).unwrap(),
+            ..Default::default()
+        });
+        let mut bot = Organism::with_program(
+            100,
+            0,
+            Program {
+                code: [OpCode::MoveRelative; CODE_SIZE],
+            },
+        );
+        bot.registers[2] = u8::from(Direction::Up);
+        world.field[(0, 5)] = WorldCell::Organism(Box::new(bot));
+
+        // move into the top edge fails, so moving down afterwards is not a reversal
+        world.tick();
+        match &mut world.field[(0, 5)] {
+            WorldCell::Organism(bot) => bot.registers[2] = u8::from(Direction::Down),
+            _ => panic!("organism has moved through the edge"),
+        }
+        world.tick();
+
+        assert!(matches!(world.field[(1, 5)], WorldCell::Organism(..)));
+    }
+
+    #[test]
+    fn test_display_shows_minerals() {
+        let bot = Organism::with_program(
+            100,
+            42,
+            Program {
+                code: [OpCode::Clone; CODE_SIZE],
+            },
+        );
+        let text = format!("{}", bot);
+
+        assert!(text.contains("minerals: 42"));
+        assert!(text.contains("can clone: true"));
+    }
+
+    #[test]
+    fn test_sight_range() {
+        // energy of seen organism goes to result2 whatever the range, distance to register 11
+        let energy = into_u8_fraction(100, WorldConfig::default().max_cell_size);
+        for (range, expected) in [(1, (0, 0, 1)), (3, (1, energy, 3))] {
+            let mut world = World::empty::<10, 10>(WorldConfig {
+                sight_range: range,
+                ..Default::default()
+            });
+            world.field[(2, 5)] = WorldCell::Organism(Box::new(Organism::green(100)));
+
+            let mut bot = Organism::with_program(
+                100,
+                0,
+                Program {
+                    code: [OpCode::LookRelative; CODE_SIZE],
+                },
+            );
+            bot.registers[2] = u8::from(Direction::Up);
+            bot.tick(&world, (5, 5));
+
+            assert_eq!(
+                (bot.registers[0], bot.registers[1], bot.registers[11]),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_sense_home_direction() {
+        let world = World::empty::<10, 10>(Default::default());
+        let mut code = [OpCode::SenseHomeDirection; CODE_SIZE];
+        code[0] = OpCode::SetHome;
+        let mut bot = Organism::with_program(100, 0, Program { code });
+
+        bot.tick(&world, (5, 1));
+        assert_eq!(bot.registers[0], 255);
+
+        // shortest path wraps around right edge
+        bot.tick(&world, (5, 8));
+        assert_eq!(bot.registers[0], u8::from(Direction::Right));
+        assert_eq!(bot.registers[1], 3);
+
+        bot.tick(&world, (5, 4));
+        assert_eq!(bot.registers[0], u8::from(Direction::Left));
+
+        bot.tick(&world, (2, 1));
+        assert_eq!(bot.registers[0], u8::from(Direction::Down));
+
+        let world = World::empty::<10, 10>(WorldConfig {
+            sense_cost: 3,
+            ..Default::default()
+        });
+        let mut code = [OpCode::Sythesize; CODE_SIZE];
+        code[0] = OpCode::SetHome;
+        let mut plain = Organism::with_program(100, 0, Program { code });
+        code[1] = OpCode::SenseHomeDirection;
+        let mut sensing = Organism::with_program(100, 0, Program { code });
+        plain.tick(&world, (5, 1));
+        sensing.tick(&world, (5, 1));
+        assert_eq!(plain.get_energy() - sensing.get_energy(), 3);
+    }
+
+    #[test]
+    fn test_split_off_retains_child_energy() {
+        let mut rng = thread_rng();
+
+        let mut parent = Organism::green(100);
+        let child = parent.split_off(Box::default, 50, 0, Mutation::default(), None, &mut rng);
+        assert_eq!(child.unwrap().get_energy(), 50);
+        assert_eq!(parent.get_energy(), 50);
+
+        let mut parent = Organism::green(99);
+        assert!(parent
+            .split_off(Box::default, 50, 0, Mutation::default(), None, &mut rng)
+            .is_none());
+        assert_eq!(parent.get_energy(), 99);
+    }
+
+    #[test]
+    fn test_label_is_inherited() {
+        let mut rng = thread_rng();
+
+        let mut parent = Organism::green(1000);
+        parent.set_label(Label::new("designed"));
+        let child = parent
+            .split_off(Box::default, 50, 0, Mutation::default(), None, &mut rng)
+            .unwrap();
+        let grandchild = child
+            .clone()
+            .split_off(
+                Box::default,
+                10,
+                0,
+                Mutation {
+                    chance: 1000,
+                    ..Default::default()
+                },
+                None,
+                &mut rng,
+            )
+            .unwrap();
+        assert_eq!(child.get_label().unwrap().as_str(), "designed");
+        assert_eq!(grandchild.get_label(), parent.get_label());
+
+        assert!(Label::new("label longer than limit").is_none());
+    }
+
+    #[test]
+    fn test_can_clone() {
+        let mut world = World::empty::<5, 5>(Default::default());
+        let program = Program {
+            code: [OpCode::CanClone; CODE_SIZE],
+        };
+
+        let mut bot = Organism::with_program(200, 0, program);
+        bot.tick(&world, (2, 2));
+        assert_eq!(bot.registers[0], 1);
+
+        let mut starving = Organism::with_program(50, 0, program);
+        starving.tick(&world, (2, 2));
+        assert_eq!(starving.registers[0], 0);
+
+        for pos in [(1, 2), (2, 3), (3, 2), (2, 1)] {
+            world.field[pos] = WorldCell::DeadBody(10, 0, 0);
+        }
+        bot.tick(&world, (2, 2));
+        assert_eq!(bot.registers[0], 0);
+    }
+
+    #[test]
+    fn test_adaptive_budget() {
+        let config = WorldConfig {
+            adaptive_budget: true,
+            ..Default::default()
+        };
+        let world = World::empty::<5, 5>(config);
+
+        // every add increments register 0 by register 1
+        let increment = OpCode::Add(1.into());
+        let complex = Program {
+            code: [increment; CODE_SIZE],
+        };
+        let mut trivial = complex;
+        trivial.code[1] = OpCode::JumpUnconditional(255);
+
+        let mut complex = Organism::with_program(100, 0, complex);
+        let mut trivial = Organism::with_program(100, 0, trivial);
+        assert_eq!(complex.complexity, CODE_SIZE);
+        assert_eq!(trivial.complexity, 2);
+
+        for bot in [&mut complex, &mut trivial] {
+            bot.registers[1] = 1;
+            bot.tick(&world, (2, 2));
+        }
+        assert_eq!(complex.registers[0], 64);
+        assert_eq!(trivial.registers[0], 8);
+    }
+
+    #[test]
+    fn test_store_minerals() {
+        let config = WorldConfig {
+            mineral_storage_efficiency: 50,
+            max_minerals: 100,
+            ..Default::default()
+        };
+        let world = World::empty::<5, 5>(config);
+        let program = Program {
+            code: [OpCode::StoreMinerals; CODE_SIZE],
+        };
+
+        let mut bot = Organism::with_program(100, 0, program);
+        bot.registers[0] = 40;
+        bot.tick(&world, (2, 2));
+        assert_eq!((bot.get_energy(), bot.get_minerals()), (60, 20));
+
+        let mut bot = Organism::with_program(100, 95, program);
+        bot.registers[0] = 40;
+        bot.tick(&world, (2, 2));
+        assert_eq!((bot.get_energy(), bot.get_minerals()), (90, 100));
+    }
+
+    #[test]
+    fn test_broadcast_receive() {
+        let mut world = World::empty::<5, 5>(Default::default());
+
+        let mut sender = Organism::with_program(
+            100,
+            0,
+            Program {
+                code: [OpCode::Broadcast; CODE_SIZE],
+            },
+        );
+        sender.registers[0] = 42;
+        sender.tick(&world, (1, 2));
+        world.field[(1, 2)] = WorldCell::Organism(Box::new(sender));
+
+        // receiver listens in direction of register 2, which is up
+        let mut receiver = Organism::with_program(
+            100,
+            0,
+            Program {
+                code: [OpCode::Receive; CODE_SIZE],
+            },
+        );
+        receiver.tick(&world, (2, 2));
+        assert_eq!(receiver.registers[0], 42);
+
+        receiver.tick(&world, (3, 2));
+        assert_eq!(receiver.registers[0], 0);
+    }
+
+    #[test]
+    fn test_compare_energy() {
+        let mut world = World::empty::<5, 5>(Default::default());
+        world.field[(1, 2)] = WorldCell::Organism(Box::new(Organism::green(50)));
+        world.field[(2, 3)] = WorldCell::Organism(Box::new(Organism::green(102)));
+        world.field[(3, 2)] = WorldCell::Organism(Box::new(Organism::green(200)));
+        world.field[(2, 1)] = WorldCell::DeadBody(100, 0, 0);
+
+        let mut bot = Organism::with_program(
+            100,
+            0,
+            Program {
+                code: [OpCode::CompareEnergy; CODE_SIZE],
+            },
+        );
+        let mut codes = vec![];
+        for direction in Direction::ALL {
+            bot.registers[2] = direction.into();
+            bot.tick(&world, (2, 2));
+            codes.push(bot.registers[0]);
+        }
+        assert_eq!(codes, vec![0, 1, 2, 255]);
+
+        bot.registers[2] = Direction::Up.into();
+        bot.tick(&world, (0, 0));
+        assert_eq!(bot.registers[0], 255);
+    }
+
+    #[test]
+    fn test_sense_crowd_direction() {
+        let config = WorldConfig {
+            sight_range: 3,
+            ..Default::default()
+        };
+        let mut world = World::empty::<9, 9>(config);
+        let program = Program {
+            code: [OpCode::SenseCrowdDirection; CODE_SIZE],
+        };
+
+        let mut bot = Organism::with_program(100, 0, program);
+        bot.tick(&world, (4, 4));
+        assert_eq!(bot.registers[0], 255);
+
+        for pos in [(3, 6), (4, 7), (5, 6), (4, 5)] {
+            world.field[pos] = WorldCell::Organism(Box::new(Organism::green(100)));
+        }
+        world.field[(2, 4)] = WorldCell::Organism(Box::new(Organism::green(100)));
+        world.field[(4, 2)] = WorldCell::Organism(Box::new(Organism::green(100)));
+
+        bot.tick(&world, (4, 4));
+        assert_eq!(bot.registers[0], u8::from(Direction::Right));
+    }
+
+    #[test]
+    fn test_sense_time() {
+        let config = WorldConfig {
+            day_length: 10,
+            ..Default::default()
+        };
+        let mut world = World::empty::<5, 5>(config);
+        let mut bot = Organism::with_program(
+            100,
+            0,
+            Program {
+                code: [OpCode::SenseTime; CODE_SIZE],
+            },
+        );
+
+        let mut sensed = vec![];
+        while !world.get_iteration().is_multiple_of(10) {
+            world.tick();
+        }
+        for _ in 0..12 {
+            bot.tick(&world, (2, 2));
+            sensed.push(bot.registers[0]);
+            world.tick();
+        }
+        assert_eq!(
+            sensed,
+            vec![0, 25, 51, 76, 102, 127, 153, 178, 204, 229, 0, 25]
+        );
+
+        world.config.day_length = 0;
+        bot.tick(&world, (2, 2));
+        assert_eq!(bot.registers[0], world.get_iteration() as u8);
+    }
+
+    #[test]
+    fn test_clone_with_minerals_rates() {
+        let world = World::empty::<5, 5>(Default::default());
+        let mut bot = Organism::with_program(
+            1024,
+            0,
+            Program {
+                code: [OpCode::CloneWithMinerals; CODE_SIZE],
+            },
+        );
+        bot.add_minerals(100, 100);
+
+        bot.registers[0] = 255;
+        bot.registers[1] = 0;
+        assert!(matches!(
+            bot.tick(&world, (2, 2)),
+            Some(OrganismAction::TryClone(510, 0, _))
+        ));
+
+        bot.registers[0] = 0;
+        bot.registers[1] = 255;
+        assert!(matches!(
+            bot.tick(&world, (2, 2)),
+            Some(OrganismAction::TryClone(energy, 100, _)) if energy == world.config.start_energy
+        ));
+    }
+
+    #[test]
+    fn test_random_direction_is_uniform() {
+        let world = World::seeded::<5, 5>(Default::default(), 7);
+        let mut bot = Organism::with_program(
+            100_000,
+            0,
+            Program {
+                code: [OpCode::RandomDirection; CODE_SIZE],
+            },
+        );
+
+        let mut counts = [0usize; 4];
+        for _ in 0..4000 {
+            bot.tick(&world, (2, 2));
+            counts[u8::from(bot.get_direction()) as usize] += 1;
+        }
+        for count in counts {
+            assert!((900..1100).contains(&count), "{counts:?}");
+        }
+    }
+
+    #[test]
+    fn test_synthesis_threshold() {
+        let config = WorldConfig {
+            light_behaviour: |i| if i == 0 { 5 } else { 1 },
+            synthesis_threshold: 2,
+            ..Default::default()
+        };
+        let world = World::empty::<5, 5>(config);
+        let mut bot = Organism::with_program(
+            100,
+            0,
+            Program {
+                code: [OpCode::Sythesize; CODE_SIZE],
+            },
+        );
+        bot.registers[0] = 42;
+
+        bot.tick(&world, (3, 2));
+        assert_eq!(bot.get_energy(), 100);
+        assert_eq!(bot.registers[10], 1);
+
+        bot.tick(&world, (0, 2));
+        assert_eq!(bot.get_energy(), 105);
+        assert_eq!(bot.registers[10], 0);
+        assert_eq!(bot.registers[0], 42);
+    }
+
+    #[test]
+    fn test_sense_dead_density() {
+        let mut world = World::empty::<5, 5>(Default::default());
+        let mut bot = Organism::with_program(
+            100,
+            0,
+            Program {
+                code: [OpCode::SenseDeadDensity; CODE_SIZE],
+            },
+        );
+
+        bot.tick(&world, (2, 2));
+        assert_eq!(bot.registers[0], 0);
+
+        for pos in [(1, 2), (2, 1), (3, 2)] {
+            world.field[pos] = WorldCell::DeadBody(10, 0, 0);
+        }
+        world.field[(2, 3)] = WorldCell::Organism(Box::new(Organism::green(100)));
+        world.field[(1, 1)] = WorldCell::DeadBody(10, 0, 0);
+        bot.tick(&world, (2, 2));
+        assert_eq!(bot.registers[0], 3);
+    }
+
+    #[test]
+    fn test_sense_energy_trend() {
+        let config = WorldConfig {
+            sense_cost: 0,
+            ..Default::default()
+        };
+        let world = World::empty::<5, 5>(config);
+        let mut bot = Organism::with_program(
+            100,
+            0,
+            Program {
+                code: [OpCode::SenseEnergyTrend; CODE_SIZE],
+            },
+        );
+
+        bot.tick(&world, (2, 2));
+        assert_eq!(bot.registers[0], 1);
+
+        bot.add_energy(10);
+        bot.tick(&world, (2, 2));
+        assert_eq!(bot.registers[0], 2);
+
+        bot.decrease_energy(20);
+        bot.tick(&world, (2, 2));
+        assert_eq!(bot.registers[0], 0);
+    }
+
+    #[test]
+    fn test_sense_dimensions() {
+        let program = Program {
+            code: [OpCode::SenseDimensions; CODE_SIZE],
+        };
+        let mut bot = Organism::with_program(100, 0, program);
+
+        let world = World::empty::<10, 20>(Default::default());
+        bot.tick(&world, (2, 2));
+        assert_eq!(bot.registers[..2], [10, 20]);
+
+        let world = World::empty::<300, 7>(Default::default());
+        bot.tick(&world, (2, 2));
+        assert_eq!(bot.registers[..2], [255, 7]);
+    }
+
+    #[test]
+    fn test_relative_turns() {
+        let world = World::empty::<5, 5>(Default::default());
+        let mut code = [OpCode::Sythesize; CODE_SIZE];
+        code[0] = OpCode::TurnRight;
+        code[1] = OpCode::TurnRight;
+        code[3] = OpCode::TurnLeft;
+        let mut bot = Organism::with_program(100, 0, Program { code });
+        bot.registers[2] = Direction::Up.into();
+
+        bot.tick(&world, (2, 2));
+        assert_eq!(bot.get_direction(), Direction::Down);
+
+        bot.tick(&world, (2, 2));
+        assert_eq!(bot.get_direction(), Direction::Right);
+    }
+
+    #[test]
+    fn test_sense_mineral_headroom() {
+        let config = WorldConfig {
+            max_minerals: 100,
+            ..Default::default()
+        };
+        let world = World::empty::<5, 5>(config);
+        let program = Program {
+            code: [OpCode::SenseMineralHeadroom; CODE_SIZE],
+        };
+
+        let mut bot = Organism::with_program(100, 0, program);
+        bot.tick(&world, (2, 2));
+        assert_eq!(bot.registers[0], 255);
+
+        let mut bot = Organism::with_program(100, 90, program);
+        bot.tick(&world, (2, 2));
+        assert_eq!(bot.registers[0], 25);
+    }
+
+    #[test]
+    fn test_sense_openness() {
+        let mut world = World::empty::<10, 10>(WorldConfig {
+            sight_range: 4,
+            wrap_rows: false,
+            ..Default::default()
+        });
+        // dead bodies wall the organism off from above and from the right,
+        // bottom edge is two cells away and nothing blocks the view to the left
+        world.field[(3, 5)] = WorldCell::DeadBody(10, 0, 0);
+        world.field[(7, 6)] = WorldCell::DeadBody(10, 0, 0);
+        let program = Program {
+            code: [OpCode::SenseOpenness; CODE_SIZE],
+        };
+
+        for (direction, expected) in [
+            (Direction::Up, 3),
+            (Direction::Right, 0),
+            (Direction::Down, 2),
+            (Direction::Left, 4),
+        ] {
+            let mut bot = Organism::with_program(100, 0, program);
+            bot.registers[2] = direction.into();
+            bot.tick(&world, (7, 5));
+            assert_eq!(bot.registers[0], expected, "{:?}", direction);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+use super::world::WorldConfig;
+
+/// environment parameter shaped by a gradient over depth
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GradientTarget {
+    Light,
+    Minerals,
+}
+
+/// named gradient shapes, light decreases with depth and minerals decrease
+/// with distance from the bottom row, linear ones reach zero 50 rows away
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigPreset {
+    Linear,
+    Exponential,
+    Stepped,
+}
+
+fn light_linear(i: usize) -> usize {
+    (50usize.saturating_sub(i) * 4) / 50
+}
+
+fn light_exponential(i: usize) -> usize {
+    8usize.checked_shr((i / 5) as u32).unwrap_or(0)
+}
+
+fn light_stepped(i: usize) -> usize {
+    3usize.saturating_sub(i / 10)
+}
+
+impl ConfigPreset {
+    pub fn light_function(self) -> fn(usize) -> usize {
+        match self {
+            ConfigPreset::Linear => light_linear,
+            ConfigPreset::Exponential => light_exponential,
+            ConfigPreset::Stepped => light_stepped,
+        }
+    }
+
+    /// minerals mirror light, as they are given distance from the bottom row
+    pub fn minerals_function(self) -> fn(usize) -> usize {
+        self.light_function()
+    }
+}
+
+impl WorldConfig {
+    pub fn apply_gradient(&mut self, target: GradientTarget, preset: ConfigPreset) {
+        match target {
+            GradientTarget::Light => self.light_behaviour = preset.light_function(),
+            GradientTarget::Minerals => self.minerals_behaviour = preset.minerals_function(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ConfigPreset, GradientTarget};
+    use crate::cells::world::World;
+
+    #[test]
+    fn test_light_preset_switch() {
+        let mut world = World::empty::<10, 50>(Default::default());
+        assert_eq!(world.get_light(0), 3);
+
+        world
+            .config
+            .apply_gradient(GradientTarget::Light, ConfigPreset::Exponential);
+
+        let profile: Vec<usize> = (0..25).step_by(5).map(|i| world.get_light(i)).collect();
+        assert_eq!(profile, vec![8, 4, 2, 1, 0]);
+        assert_eq!(world.get_light(49), 0);
+    }
+
+    #[test]
+    fn test_minerals_follow_world_bottom() {
+        let mut world = World::empty::<10, 80>(Default::default());
+        assert_eq!(world.get_minerals(79), 3);
+        assert_eq!(world.get_minerals(0), 0);
+
+        world
+            .config
+            .apply_gradient(GradientTarget::Minerals, ConfigPreset::Linear);
+        assert_eq!(world.get_minerals(79), 4);
+        assert_eq!(world.get_minerals(29), 0);
+    }
+}
@@ -0,0 +1,63 @@
+use super::world::{World, WorldConfig};
+
+/// names accepted by [World::benchmark_scenario]
+pub const BENCHMARK_SCENARIOS: [&str; 3] = ["dense_green", "predator_prey", "sparse_random"];
+
+/// every cell of the world is occupied by a green organism,
+/// stresses photosynthesis and splitting into no free space
+fn dense_green(seed: u64) -> World {
+    let mut world = World::seeded::<100, 50>(WorldConfig::default(), seed);
+    world.populate_green(100 * 50);
+    world
+}
+
+/// half of the world filled with green organisms and a smaller
+/// amount of random ones that are likely to eat them
+fn predator_prey(seed: u64) -> World {
+    let mut world = World::seeded::<100, 50>(WorldConfig::default(), seed);
+    world.populate_green(2000);
+    world.populate_random(500);
+    world
+}
+
+/// few random organisms in mostly empty world, stresses movement
+/// and placement of children
+fn sparse_random(seed: u64) -> World {
+    let mut world = World::seeded::<100, 50>(WorldConfig::default(), seed);
+    world.populate_random(250);
+    world
+}
+
+impl World {
+    /// standardized populated world for performance measurements,
+    /// None if scenario name is unknown (see [BENCHMARK_SCENARIOS])
+    pub fn benchmark_scenario(name: &str, seed: u64) -> Option<World> {
+        match name {
+            "dense_green" => Some(dense_green(seed)),
+            "predator_prey" => Some(predator_prey(seed)),
+            "sparse_random" => Some(sparse_random(seed)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BENCHMARK_SCENARIOS;
+    use crate::cells::world::World;
+
+    #[test]
+    fn test_scenarios_are_deterministic() {
+        let expected = [5000, 2500, 250];
+
+        for (name, population) in BENCHMARK_SCENARIOS.iter().zip(expected) {
+            let first = World::benchmark_scenario(name, 42).unwrap();
+            let second = World::benchmark_scenario(name, 42).unwrap();
+
+            assert_eq!(first.count_alive(), population, "{}", name);
+            assert_eq!(first.checksum(), second.checksum(), "{}", name);
+        }
+
+        assert!(World::benchmark_scenario("unknown", 42).is_none());
+    }
+}
@@ -0,0 +1,221 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use super::{code::CODE_SIZE, organism::MAX_ACTIONS, world::WorldConfig};
+
+/// numeric config parameter that can be changed at runtime
+pub struct Setting {
+    pub name: &'static str,
+    pub min: usize,
+    pub max: usize,
+    get: fn(&WorldConfig) -> usize,
+    set: fn(&mut WorldConfig, usize),
+}
+
+macro_rules! setting {
+    ($name:ident, $min:expr, $max:expr) => {
+        Setting {
+            name: stringify!($name),
+            min: $min,
+            max: $max,
+            get: |config| config.$name,
+            set: |config, value| config.$name = value,
+        }
+    };
+}
+
+/// single source of truth for runtime settings, used both
+/// by the schema endpoint and for validation in `set_setting`
+pub static SETTINGS: &[Setting] = &[
+    setting!(mutation_chance, 0, 1000),
+    setting!(start_energy_jitter, 0, 10_000),
+    setting!(max_cell_size, 1, 100_000),
+    setting!(max_minerals, 0, 100_000),
+    setting!(mineral_storage_efficiency, 0, 100),
+    setting!(day_length, 0, 100_000),
+    setting!(night_light_percent, 0, 100),
+    setting!(senescence_rate, 0, 1000),
+    setting!(depth_mismatch_penalty, 0, 10_000),
+    setting!(complexity_cost, 0, 10_000),
+    setting!(clutch_size, 1, 4),
+    setting!(actions_per_tick, 1, MAX_ACTIONS),
+    setting!(kin_genetic_threshold, 0, CODE_SIZE),
+    setting!(sight_range, 1, 100),
+    setting!(energy_speed_scaling, 0, 10_000),
+    setting!(max_speed, 1, 100),
+    setting!(sense_cost, 0, 1000),
+    setting!(armor_cost, 0, 10_000),
+    setting!(attack_power_cost, 0, 10_000),
+    setting!(armor_decay, 0, 255),
+    setting!(link_share_rate, 0, 100),
+    setting!(bottleneck_threshold, 0, 1_000_000),
+    setting!(population_floor, 0, 1_000_000),
+    setting!(idle_timeout_secs, 0, 86_400),
+    setting!(max_instruction_budget, 16, 256),
+    setting!(occlusion_per_energy, 0, 1000),
+    setting!(territory_lifetime, 0, 100_000),
+    setting!(census_interval, 0, 1_000_000),
+    setting!(min_clone_parent_energy, 0, 100_000),
+    setting!(germination_light, 0, 1000),
+    setting!(heatmap_retain, 0, 100),
+    setting!(synthesis_threshold, 0, 1000),
+    setting!(prey_similarity_threshold, 0, 64),
+    setting!(digestion_ticks, 0, 1000),
+    setting!(graze_amount, 0, 1000),
+    setting!(graze_cost, 0, 1000),
+    setting!(glow_light, 0, 1000),
+    setting!(glow_cost, 0, 1000),
+];
+
+/// boolean config parameter that can be toggled at runtime
+pub struct Flag {
+    pub name: &'static str,
+    get: fn(&WorldConfig) -> bool,
+    set: fn(&mut WorldConfig, bool),
+}
+
+macro_rules! flag {
+    ($name:ident) => {
+        Flag {
+            name: stringify!($name),
+            get: |config| config.$name,
+            set: |config, value| config.$name = value,
+        }
+    };
+}
+
+pub static FLAGS: &[Flag] = &[
+    flag!(deterministic_combat),
+    flag!(movement_inertia),
+    flag!(blocked_action_feedback),
+    flag!(sexual_reproduction),
+    flag!(adaptive_budget),
+    flag!(wrap_columns),
+    flag!(wrap_rows),
+    flag!(kin_safe_eating),
+    flag!(death_inheritance),
+    flag!(inherit_home),
+    flag!(allow_multicell),
+    flag!(allow_fusion),
+    flag!(allow_bioluminescence),
+    flag!(always_run),
+    flag!(track_energy_flow),
+    flag!(parallel_tick),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SettingError {
+    UnknownKey(String),
+    OutOfRange { min: usize, max: usize },
+}
+
+impl std::fmt::Display for SettingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingError::UnknownKey(key) => write!(f, "parameter not found: {}", key),
+            SettingError::OutOfRange { min, max } => {
+                write!(f, "value should be in range {}..={}", min, max)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SettingSchema {
+    pub name: &'static str,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub min: usize,
+    pub max: usize,
+    pub value: usize,
+}
+
+impl WorldConfig {
+    pub fn set_setting(&mut self, key: &str, value: usize) -> Result<(), SettingError> {
+        let setting = SETTINGS
+            .iter()
+            .find(|setting| setting.name == key)
+            .ok_or_else(|| SettingError::UnknownKey(key.to_string()))?;
+
+        if !(setting.min..=setting.max).contains(&value) {
+            return Err(SettingError::OutOfRange {
+                min: setting.min,
+                max: setting.max,
+            });
+        }
+
+        (setting.set)(self, value);
+        Ok(())
+    }
+
+    pub fn set_flag(&mut self, key: &str, value: bool) -> Result<(), SettingError> {
+        let flag = FLAGS
+            .iter()
+            .find(|flag| flag.name == key)
+            .ok_or_else(|| SettingError::UnknownKey(key.to_string()))?;
+
+        (flag.set)(self, value);
+        Ok(())
+    }
+
+    pub fn flags(&self) -> BTreeMap<&'static str, bool> {
+        FLAGS
+            .iter()
+            .map(|flag| (flag.name, (flag.get)(self)))
+            .collect()
+    }
+
+    /// numeric settings followed by flags, flags are reported as integers in 0..=1
+    pub fn settings_schema(&self) -> Vec<SettingSchema> {
+        let settings = SETTINGS.iter().map(|setting| SettingSchema {
+            name: setting.name,
+            kind: "integer",
+            min: setting.min,
+            max: setting.max,
+            value: (setting.get)(self),
+        });
+        let flags = FLAGS.iter().map(|flag| SettingSchema {
+            name: flag.name,
+            kind: "boolean",
+            min: 0,
+            max: 1,
+            value: (flag.get)(self) as usize,
+        });
+        settings.chain(flags).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SettingError;
+    use crate::cells::world::WorldConfig;
+
+    #[test]
+    fn test_schema_keys_are_settable() {
+        let mut config = WorldConfig::default();
+
+        for entry in config.settings_schema() {
+            if entry.kind == "boolean" {
+                assert_eq!(config.set_flag(entry.name, true), Ok(()));
+                assert_eq!(config.set_flag(entry.name, false), Ok(()));
+            } else {
+                assert_eq!(config.set_setting(entry.name, entry.max), Ok(()));
+                assert_eq!(config.set_setting(entry.name, entry.min), Ok(()));
+            }
+        }
+
+        for entry in config.settings_schema() {
+            assert_eq!(entry.value, entry.min, "{}", entry.name);
+        }
+
+        assert!(matches!(
+            config.set_setting("night_light_percent", 101),
+            Err(SettingError::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            config.set_setting("no_such_key", 1),
+            Err(SettingError::UnknownKey(..))
+        ));
+    }
+}
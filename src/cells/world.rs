@@ -1,44 +1,394 @@
 use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, VecDeque},
     fmt::Display,
     mem,
     ops::{Index, IndexMut},
 };
 
-use rand::{distributions::Bernoulli, prelude::SliceRandom, thread_rng, Rng};
-use serde::{Deserialize, Serialize};
+use parking_lot::{Mutex, MutexGuard};
+use rand::{
+    distributions::Bernoulli, prelude::SliceRandom, rngs::StdRng, thread_rng, Rng, SeedableRng,
+};
+use rayon::prelude::*;
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
 
 use crate::cachealloc::ObjectCache;
 
-use super::organism::{Direction, Organism, OrganismAction};
+use super::{
+    code::{Mutation, MutationKind, Program},
+    heatmap::Heatmap,
+    organism::{Actions, Direction, Label, Organism, OrganismAction},
+};
 
-#[derive(Clone, Debug)]
+/// Behaviour functions are not serialized, deserialized config holds default ones
+/// until [WorldConfig::reattach_behaviours] is called
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct WorldConfig {
     pub start_energy: usize,
+    /// maximal difference between energy of spawned organism and `start_energy`
+    pub start_energy_jitter: usize,
     pub dead_energy: usize,
+    #[serde(skip, default = "default_split_behaviour")]
     pub split_behaviour: fn(usize, usize) -> Result<(usize, usize), ()>,
+    #[serde(skip, default = "default_light_behaviour")]
     pub light_behaviour: fn(usize) -> usize,
+    /// minerals available at given distance from the bottom row
+    #[serde(skip, default = "default_minerals_behaviour")]
     pub minerals_behaviour: fn(usize) -> usize,
     pub mutation_chance: usize,
+    /// weights of substitution and insertion/deletion mutations
+    pub mutation_kind: MutationKind,
     pub aging_mutation_freq: Bernoulli,
     pub max_cell_size: usize,
     pub max_minerals: usize,
+    /// optional mineral capacity depending on organism energy, overrides `max_minerals`
+    #[serde(skip)]
+    pub mineral_capacity_fn: Option<fn(usize) -> usize>,
     pub attack_cost: usize,
+    /// number of ticks, counting the one of successful eat, during which eating is a no-op
+    pub digestion_ticks: usize,
+    /// energy taken from living neighbour by graze instruction
+    pub graze_amount: usize,
+    /// energy spent on every graze attempt
+    pub graze_cost: usize,
+    /// percentage of energy turned into minerals by store minerals instruction
+    pub mineral_storage_efficiency: usize,
+    /// whether attack succeeds exactly when attacker is heavier than its target,
+    /// target armor is ignored in this mode
+    pub deterministic_combat: bool,
+    /// energy spent on setting armor to its maximum level, scaled down for lower levels
+    pub armor_cost: usize,
+    /// energy spent on raising attack power to its maximum level, scaled down for lower levels
+    pub attack_power_cost: usize,
+    /// length of day/night cycle in ticks, 0 disables the cycle
+    pub day_length: usize,
+    /// percentage of light available during night
+    pub night_light_percent: usize,
+    /// energy lost every tick per 1000 ticks of organism age
+    pub senescence_rate: usize,
+    /// energy spent by every executed sensing instruction
+    pub sense_cost: usize,
+    /// armor points lost every tick
+    pub armor_decay: usize,
+    /// number of cells visible to look instruction
+    pub sight_range: usize,
+    /// whether organisms have to turn before moving in opposite direction
+    pub movement_inertia: bool,
+    /// energy required for every extra cell travelled by single move, 0 disables
+    pub energy_speed_scaling: usize,
+    /// maximal number of cells travelled by single move
+    pub max_speed: usize,
+    /// whether moves and attacks into world edge are reported to organism via register 9
+    pub blocked_action_feedback: bool,
+    /// whether clone instruction mixes parent program with program of a neighbour
+    pub sexual_reproduction: bool,
+    /// whether instruction budget grows with number of reachable instructions
+    pub adaptive_budget: bool,
+    /// upper limit of adaptive instruction budget
+    pub max_instruction_budget: usize,
+    /// light units blocked for cells below an organism per 100 energy of that organism,
+    /// 0 disables occlusion
+    pub occlusion_per_energy: usize,
+    /// whether crossing left or right edge leads to the opposite side of the world
+    pub wrap_columns: bool,
+    /// whether crossing top or bottom edge leads to the opposite side of the world
+    pub wrap_rows: bool,
+    /// resources gained (or lost when negative) every tick by organisms
+    /// in the first and the last row, applies only when rows do not wrap
+    pub edge_flux: EdgeFlux,
+    /// energy drained per tick for every 100 rows between organism
+    /// and its preferred depth, 0 disables
+    pub depth_mismatch_penalty: usize,
+    /// energy drained per tick for every 100 reachable instructions of organism's program,
+    /// 0 disables
+    pub complexity_cost: usize,
+    /// maximal number of differing signature bits for neighbour to be sensed as last prey
+    pub prey_similarity_threshold: usize,
+    /// maximal number of differing genes for organisms of different lineages
+    /// to still be considered kin, 0 means only lineage is compared
+    pub kin_genetic_threshold: usize,
+    /// whether eat instruction targeting kin does nothing
+    pub kin_safe_eating: bool,
+    /// whether energy of dying organism, `dead_energy` of its body included, is passed
+    /// to adjacent living kin, corpse then holds only whatever could not be split
+    pub death_inheritance: bool,
+    /// whether children remember home position of their parent
+    pub inherit_home: bool,
+    /// maximal number of children produced by single clone instruction,
+    /// children share energy of what would be a single child
+    pub clutch_size: usize,
+    /// light at which dormant spores wake up
+    pub germination_light: usize,
+    /// light below which synthesis yields nothing, synthesis reports into register 10
+    /// whether it was too dark (1) or not (0)
+    pub synthesis_threshold: usize,
+    /// seconds without human activity after which simulation stops being throttled
+    /// to a display-friendly tick rate
+    pub idle_timeout_secs: usize,
+    /// never throttle simulation, regardless of human activity
+    pub always_run: bool,
+    /// accumulate energy entering and leaving organisms during tick, see [EnergyFlow]
+    pub track_energy_flow: bool,
+    /// percentage of birth and death heatmap density retained every tick
+    pub heatmap_retain: usize,
+    /// population below which green organisms are spawned after tick, 0 disables
+    pub population_floor: usize,
+    /// number of acting instructions organism may perform in a single tick, at most
+    /// [MAX_ACTIONS](super::organism::MAX_ACTIONS)
+    pub actions_per_tick: usize,
+    /// where resources of self-destructed organism go
+    pub self_destruct_mode: SelfDestructMode,
+    /// organisms decide on their actions in parallel looking at the field as it was before
+    /// any of them acted, actions are then applied in row-major order so organism with
+    /// the lowest linear index wins a contested cell. Runs are as reproducible as with
+    /// sequential tick, but take a different course. Of what neighbours did to organism
+    /// between planning and applying, only energy, minerals and attack register are kept,
+    /// which are all actions can change in other organisms
+    pub parallel_tick: bool,
+    /// order in which free cells are filled by populate
+    pub placement_order: PlacementOrder,
+    /// energy below which clone instruction always fails
+    pub min_clone_parent_energy: usize,
+    /// whether cloned children stay linked to their parents
+    pub allow_multicell: bool,
+    /// whether fuse instruction merges organism with adjacent kin
+    pub allow_fusion: bool,
+    /// whether glow instruction lights up organism's cell and its neighbours
+    pub allow_bioluminescence: bool,
+    /// light added by glow instruction during the next tick
+    pub glow_light: usize,
+    /// energy spent by glow instruction
+    pub glow_cost: usize,
+    /// percentage of energy difference equalized between linked cells every tick
+    pub link_share_rate: usize,
+    /// number of ticks territory claim lasts
+    pub territory_lifetime: usize,
+    /// number of ticks between census rows appended to csv log, 0 disables census
+    pub census_interval: usize,
+    /// population size below which surviving genomes are snapshotted, 0 disables snapshots
+    pub bottleneck_threshold: usize,
+}
+
+impl WorldConfig {
+    /// amount of minerals organism with given energy can store
+    pub fn mineral_capacity(&self, energy: usize) -> usize {
+        match self.mineral_capacity_fn {
+            Some(capacity) => capacity(energy),
+            None => self.max_minerals,
+        }
+    }
+
+    /// copies behaviour functions from other config
+    pub fn reattach_behaviours(&mut self, other: &WorldConfig) {
+        self.split_behaviour = other.split_behaviour;
+        self.light_behaviour = other.light_behaviour;
+        self.minerals_behaviour = other.minerals_behaviour;
+        self.mineral_capacity_fn = other.mineral_capacity_fn;
+    }
+}
+
+fn default_split_behaviour() -> fn(usize, usize) -> Result<(usize, usize), ()> {
+    WorldConfig::default().split_behaviour
+}
+
+fn default_light_behaviour() -> fn(usize) -> usize {
+    WorldConfig::default().light_behaviour
+}
+
+fn default_minerals_behaviour() -> fn(usize) -> usize {
+    WorldConfig::default().minerals_behaviour
+}
+
+impl Default for WorldConfig {
+    fn default() -> Self {
+        WorldConfig {
+            start_energy: 40,
+            start_energy_jitter: 0,
+            dead_energy: 20,
+            split_behaviour: |energy, minerals| {
+                if energy > 200 {
+                    Ok((energy / 2, minerals / 2))
+                } else {
+                    Err(())
+                }
+            },
+            light_behaviour: |i| 3usize.saturating_sub(i / 10),
+            minerals_behaviour: |distance_from_bottom| {
+                3usize.saturating_sub(distance_from_bottom / 10)
+            },
+            mutation_chance: 1,
+            mutation_kind: MutationKind::default(),
+            aging_mutation_freq: Bernoulli::from_ratio(1, 1000).unwrap(),
+            max_cell_size: 500,
+            max_minerals: 100,
+            mineral_capacity_fn: None,
+            attack_cost: 10,
+            digestion_ticks: 0,
+            graze_amount: 10,
+            graze_cost: 2,
+            mineral_storage_efficiency: 50,
+            deterministic_combat: false,
+            armor_cost: 10,
+            attack_power_cost: 10,
+            day_length: 0,
+            night_light_percent: 0,
+            senescence_rate: 0,
+            sense_cost: 0,
+            armor_decay: 16,
+            sight_range: 1,
+            movement_inertia: false,
+            energy_speed_scaling: 0,
+            max_speed: 3,
+            blocked_action_feedback: false,
+            sexual_reproduction: false,
+            adaptive_budget: false,
+            max_instruction_budget: 64,
+            occlusion_per_energy: 0,
+            wrap_columns: true,
+            wrap_rows: false,
+            edge_flux: Default::default(),
+            prey_similarity_threshold: 4,
+            depth_mismatch_penalty: 0,
+            complexity_cost: 0,
+            kin_genetic_threshold: 0,
+            kin_safe_eating: false,
+            death_inheritance: false,
+            inherit_home: false,
+            clutch_size: 1,
+            min_clone_parent_energy: 0,
+            self_destruct_mode: SelfDestructMode::Feed,
+            placement_order: PlacementOrder::Shuffled,
+            actions_per_tick: 1,
+            parallel_tick: false,
+            population_floor: 0,
+            heatmap_retain: 95,
+            idle_timeout_secs: 2,
+            always_run: false,
+            track_energy_flow: false,
+            germination_light: 1,
+            synthesis_threshold: 0,
+            allow_multicell: false,
+            allow_fusion: false,
+            allow_bioluminescence: false,
+            glow_light: 3,
+            glow_cost: 5,
+            link_share_rate: 10,
+            territory_lifetime: 100,
+            census_interval: 0,
+            bottleneck_threshold: 0,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum WorldCell {
     Empty,
     Organism(Box<Organism>),
-    DeadBody(usize, usize),
+    /// energy, minerals and iteration of death
+    #[serde(deserialize_with = "deserialize_dead_body")]
+    DeadBody(usize, usize, usize),
+}
+
+/// accepts dead bodies saved before iteration of death was recorded,
+/// those are treated as dead since iteration 0
+fn deserialize_dead_body<'de, D>(deserializer: D) -> Result<(usize, usize, usize), D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct DeadBodyVisitor;
+
+    impl<'de> Visitor<'de> for DeadBodyVisitor {
+        type Value = (usize, usize, usize);
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "energy, minerals and optional iteration of death")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let energy = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+            let minerals = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+            let died_at = seq.next_element()?.unwrap_or(0);
+            Ok((energy, minerals, died_at))
+        }
+    }
+
+    deserializer.deserialize_tuple(3, DeadBodyVisitor)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SelfDestructMode {
+    /// resources are split between adjacent living organisms,
+    /// the rest stays as a corpse
+    Feed,
+    /// resources are split between corpses placed on organism's cell and adjacent empty cells
+    Scatter,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlacementOrder {
+    /// random free cells
+    Shuffled,
+    /// topmost rows first
+    TopDown,
+    /// bottommost rows first
+    BottomUp,
+    /// cells closest to the center of the field first
+    CenterOut,
+}
+
+/// decision of a single organism made during parallel tick
+struct Plan {
+    pos: (usize, usize),
+    /// organism as it was when decision was made
+    before: Organism,
+    /// organism after running its program
+    planned: Organism,
+    actions: Actions,
+}
+
+/// way organism has left the world
+enum Death {
+    /// regular death leaving a corpse
+    Natural,
+    /// self-destruction, resources are already distributed
+    Burst,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct EdgeFlux {
+    pub top_energy: isize,
+    pub top_minerals: isize,
+    pub bottom_energy: isize,
+    pub bottom_minerals: isize,
+}
+
+/// lineage that last marked a cell together with iteration of marking
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct TerritoryClaim {
+    pub lineage: usize,
+    pub claimed_at: usize,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct WorldField {
     pub inner: Vec<WorldCell>,
-
-    #[serde(skip_serializing)]
+    /// territory layer, may be empty for fields loaded from older saves
+    #[serde(default)]
+    pub territory: Vec<Option<TerritoryClaim>>,
+    /// per-cell multipliers of mutation chance, empty means no hotspots
     #[serde(default)]
-    cache: ObjectCache<2500>,
+    pub mutagen: Vec<usize>,
+
+    #[serde(skip)]
+    cache: ObjectCache,
     width: usize,
 }
 
@@ -57,9 +407,12 @@ impl IndexMut<(usize, usize)> for WorldField {
 }
 
 impl WorldField {
+    /// cell at given position, `None` if either coordinate is out of bounds
     pub fn get(&self, (i, j): (usize, usize)) -> Option<&WorldCell> {
-        let pos = i * self.width + j;
-        self.inner.get(pos)
+        if j >= self.width {
+            return None;
+        }
+        self.inner.get(i.checked_mul(self.width)? + j)
     }
 
     pub fn get_width(&self) -> usize {
@@ -71,37 +424,193 @@ impl WorldField {
     }
 }
 
+pub type WorldRng = StdRng;
+
+/// reasons [World::spawn_program] may refuse to place an organism
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpawnError {
+    OutOfBounds,
+    Occupied,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PopulateResult {
+    pub placed: usize,
+    pub requested: usize,
+}
+
+/// energy gained or lost by organisms during a single tick, by source
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnergyFlow {
+    pub synthesized: usize,
+    /// from living prey and corpses
+    pub eaten: usize,
+    pub minerals: usize,
+    /// upkeep, aging and other per-tick costs
+    pub decayed: usize,
+    /// transferred between organisms by share instruction
+    pub shared: usize,
+}
+
+/// number of most recent deaths whose offspring counts are kept
+pub const OFFSPRING_WINDOW: usize = 1000;
+
+/// distributions of offspring count, mapping number of children to number of organisms
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReproductionStats {
+    /// organisms that are still alive and may produce more children
+    pub living: BTreeMap<usize, usize>,
+    /// completed lifetimes of the last [OFFSPRING_WINDOW] dead organisms
+    pub completed: BTreeMap<usize, usize>,
+}
+
+/// estimated memory used by world components in bytes
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MemoryFootprint {
+    /// cell array itself
+    pub field: usize,
+    /// heap allocations of living organisms
+    pub organisms: usize,
+    pub updates: usize,
+    pub cache: usize,
+    pub territory: usize,
+    pub shade: usize,
+    pub total: usize,
+}
+
+/// state of the world sufficient to restore it on another machine,
+/// rng stream is not included
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WorldSnapshot {
+    pub config: WorldConfig,
+    pub field: WorldField,
+    pub iteration: usize,
+    updates: Vec<usize>,
+    next_lineage: usize,
+}
+
+pub type IterationHook = Box<dyn FnMut(usize) + Send + Sync>;
+pub type ActionHook = Box<dyn FnMut((usize, usize), Option<OrganismAction>) + Send + Sync>;
+
+/// optional observers invoked at fixed phases of [World::tick], unset hooks cost a single check
+#[derive(Default)]
+pub struct TickHooks {
+    /// receives iteration that is about to be simulated
+    pub before_tick: Option<IterationHook>,
+    /// receives original position of organism and action it has taken
+    pub after_action: Option<ActionHook>,
+    /// receives iteration that has just been simulated
+    pub after_tick: Option<IterationHook>,
+}
+
+/// All randomness of the simulation is drawn from world's seeded rng,
+/// so two worlds created with the same seed and fed the same api calls
+/// evolve identically. Determinism is not guaranteed for worlds created via
+/// [World::empty] (seed is drawn from entropy) and for anything happening
+/// outside of the world (eg. password generation)
 pub struct World {
     pub field: WorldField,
     iteration: usize,
     updates: Vec<usize>,
     width: usize,
+    rng: Mutex<WorldRng>,
+    /// seed world rng was last seeded with, allows replaying the run
+    seed: u64,
+    next_lineage: usize,
+    /// extra light and iteration until which it is added, see [World::boost_light]
+    light_boost: (usize, usize),
+    /// light blocked by organisms above each cell, recomputed at the start of tick
+    shade: Vec<usize>,
+    /// cells organisms were born into while plans of parallel tick are applied,
+    /// empty outside of it, see [World::tick_organisms_parallel]
+    newborn_cells: Vec<bool>,
+    /// light emitted by glowing organisms during current tick and light emitted
+    /// during previous one, which is what cells see for the whole current tick
+    glow: (Vec<usize>, Vec<usize>),
+    pub births: Heatmap,
+    pub deaths: Heatmap,
+    /// offspring counts of recently dead organisms, see [OFFSPRING_WINDOW]
+    offspring_window: VecDeque<usize>,
+    /// flow accumulated during current tick and the one of last finished tick
+    energy_flow: (Mutex<EnergyFlow>, EnergyFlow),
 
     pub config: WorldConfig,
     pub measure_steps: usize,
+    pub hooks: TickHooks,
 }
 
 impl World {
+    #[cfg(test)]
     pub fn empty<const WIDTH: usize, const HEIGHT: usize>(config: WorldConfig) -> Self {
-        let field = vec![WorldCell::Empty; WIDTH * HEIGHT];
+        Self::with_dimensions(WIDTH, HEIGHT, config)
+    }
+
+    pub fn seeded<const WIDTH: usize, const HEIGHT: usize>(config: WorldConfig, seed: u64) -> Self {
+        Self::seeded_with_dimensions(WIDTH, HEIGHT, config, seed)
+    }
+
+    /// empty world of size chosen at runtime
+    pub fn with_dimensions(width: usize, height: usize, config: WorldConfig) -> Self {
+        Self::seeded_with_dimensions(width, height, config, thread_rng().gen())
+    }
+
+    /// panics if either dimension is zero
+    pub fn seeded_with_dimensions(
+        width: usize,
+        height: usize,
+        config: WorldConfig,
+        seed: u64,
+    ) -> Self {
+        assert!(width > 0 && height > 0, "world must have at least one cell");
+        tracing::info!(seed, width, height, "world rng seeded");
+        let cells = width * height;
         World {
             field: WorldField {
-                width: WIDTH,
-                inner: field,
-                cache: Default::default(),
+                width,
+                inner: vec![WorldCell::Empty; cells],
+                territory: vec![None; cells],
+                mutagen: vec![],
+                // enough allocations to refill half of the field
+                cache: ObjectCache::with_capacity(cells / 2),
             },
             iteration: 1,
-            updates: vec![0; WIDTH * HEIGHT],
-            width: WIDTH,
+            updates: vec![0; cells],
+            width,
+            rng: Mutex::new(WorldRng::seed_from_u64(seed)),
+            seed,
+            next_lineage: 1,
+            light_boost: (0, 0),
+            shade: vec![0; cells],
+            newborn_cells: vec![],
+            glow: (vec![0; cells], vec![0; cells]),
+            births: Heatmap::default(),
+            deaths: Heatmap::default(),
+            offspring_window: VecDeque::with_capacity(OFFSPRING_WINDOW),
+            energy_flow: Default::default(),
             config,
             measure_steps: 0usize,
+            hooks: Default::default(),
         }
     }
 
+    pub fn rng(&self) -> MutexGuard<'_, WorldRng> {
+        self.rng.lock()
+    }
+
+    /// hash of serialized field, used to compare worlds for reproducibility
+    #[cfg(test)]
+    pub fn checksum(&self) -> u64 {
+        use std::{collections::hash_map::DefaultHasher, hash::Hasher};
+
+        let mut hasher = DefaultHasher::new();
+        hasher.write(&serde_json::to_vec(&self.field).unwrap());
+        hasher.finish()
+    }
+
     fn get_free_cells(&self) -> Vec<(usize, usize)> {
         let mut res = vec![];
-        for j in 0..self.field.width {
-            for i in 0..self.field.inner.len() / self.field.width {
+        for i in 0..self.get_height() {
+            for j in 0..self.get_width() {
                 if matches!(self.field[(i, j)], WorldCell::Empty) {
                     res.push((i, j));
                 }
@@ -112,40 +621,237 @@ impl World {
 
     fn populate(
         &mut self,
-        mut number_of_bots: usize,
-        bot_factory: fn(usize) -> Organism,
-    ) -> Result<(), usize> {
+        number_of_bots: usize,
+        bot_factory: fn(usize, &mut WorldRng) -> Organism,
+    ) -> PopulateResult {
         let mut free_cells = self.get_free_cells();
 
-        free_cells.shuffle(&mut thread_rng());
-
-        for (i, j) in free_cells {
-            if number_of_bots == 0 {
-                break;
+        match self.config.placement_order {
+            PlacementOrder::Shuffled => self.shuffle(&mut free_cells),
+            PlacementOrder::TopDown => free_cells.sort(),
+            PlacementOrder::BottomUp => free_cells.sort_by_key(|&(i, j)| (Reverse(i), j)),
+            PlacementOrder::CenterOut => {
+                let (center_i, center_j) = (self.get_height() / 2, self.get_width() / 2);
+                free_cells.sort_by_key(|&(i, j)| {
+                    let (di, dj) = (i.abs_diff(center_i), j.abs_diff(center_j));
+                    (di * di + dj * dj, i, j)
+                })
             }
+        }
 
+        let mut placed = 0;
+        for (i, j) in free_cells.into_iter().take(number_of_bots) {
             let mut allocation = self.field.cache.get_alloc();
-            *allocation.as_mut() = bot_factory(self.config.start_energy);
+            let energy = self.jittered_start_energy();
+            *allocation.as_mut() = bot_factory(energy, self.rng.get_mut());
+            allocation.set_lineage(self.next_lineage);
+            self.next_lineage += 1;
 
             self.field[(i, j)] = WorldCell::Organism(allocation);
-            number_of_bots -= 1;
+            placed += 1;
         }
 
-        if number_of_bots > 0 {
-            Err(number_of_bots)
-        } else {
-            Ok(())
+        PopulateResult {
+            placed,
+            requested: number_of_bots,
+        }
+    }
+
+    /// `start_energy` shifted by random amount within `start_energy_jitter`, at least 1
+    fn jittered_start_energy(&mut self) -> usize {
+        let (energy, jitter) = (self.config.start_energy, self.config.start_energy_jitter);
+        if jitter == 0 {
+            return energy;
+        }
+        self.rng
+            .get_mut()
+            .gen_range(energy.saturating_sub(jitter)..=energy + jitter)
+            .max(1)
+    }
+
+    /// places organism running given program at empty cell as a founder of a new lineage
+    pub fn spawn_program(
+        &mut self,
+        pos: (usize, usize),
+        program: Program,
+        label: Option<Label>,
+    ) -> Result<(), SpawnError> {
+        match self.field.get(pos) {
+            None => return Err(SpawnError::OutOfBounds),
+            Some(WorldCell::Empty) => {}
+            Some(_) => return Err(SpawnError::Occupied),
         }
+        let energy = self.jittered_start_energy();
+        let mut allocation = self.field.cache.get_alloc();
+        *allocation.as_mut() = Organism::with_program(energy, 0, program);
+        allocation.set_lineage(self.next_lineage);
+        allocation.set_label(label);
+        self.next_lineage += 1;
+        self.field[pos] = WorldCell::Organism(allocation);
+        Ok(())
     }
 
-    pub fn populate_green(&mut self, number_of_bots: usize) -> Result<(), usize> {
-        self.populate(number_of_bots, Organism::green)
+    pub fn populate_green(&mut self, number_of_bots: usize) -> PopulateResult {
+        self.populate(number_of_bots, |energy, _| Organism::green(energy))
     }
 
-    pub fn populate_random(&mut self, number_of_bots: usize) -> Result<(), usize> {
+    pub fn populate_random(&mut self, number_of_bots: usize) -> PopulateResult {
         self.populate(number_of_bots, Organism::random)
     }
 
+    fn record_birth(&mut self, (i, j): (usize, usize)) {
+        self.births
+            .record(i * self.width + j, self.field.inner.len());
+        if let Some(newborn) = self.newborn_cells.get_mut(i * self.width + j) {
+            *newborn = true;
+        }
+    }
+
+    /// counts death in the heatmap and reproduction stats, also used by disasters
+    pub(crate) fn record_death(&mut self, (i, j): (usize, usize), offspring_count: usize) {
+        self.deaths
+            .record(i * self.width + j, self.field.inner.len());
+        if self.offspring_window.len() == OFFSPRING_WINDOW {
+            self.offspring_window.pop_front();
+        }
+        self.offspring_window.push_back(offspring_count);
+    }
+
+    /// updates energy flow of current tick when `track_energy_flow` is enabled
+    pub fn track_energy(&self, update: impl FnOnce(&mut EnergyFlow)) {
+        if self.config.track_energy_flow {
+            update(&mut self.energy_flow.0.lock());
+        }
+    }
+
+    /// energy flow of the last finished tick
+    pub fn get_energy_flow(&self) -> EnergyFlow {
+        self.energy_flow.1
+    }
+
+    pub fn reproduction_stats(&self) -> ReproductionStats {
+        let mut living = BTreeMap::new();
+        for cell in self.field.inner.iter() {
+            if let WorldCell::Organism(bot) = cell {
+                *living.entry(bot.get_offspring_count()).or_default() += 1;
+            }
+        }
+        let mut completed = BTreeMap::new();
+        for &count in self.offspring_window.iter() {
+            *completed.entry(count).or_default() += 1;
+        }
+        ReproductionStats { living, completed }
+    }
+
+    /// spawns green organisms to bring population back to `population_floor`
+    fn enforce_population_floor(&mut self) {
+        let floor = self.config.population_floor;
+        if floor == 0 {
+            return;
+        }
+        let alive = self.count_alive();
+        if alive < floor {
+            let result = self.populate_green(floor - alive);
+            tracing::warn!(
+                iteration = self.iteration,
+                alive,
+                spawned = result.placed,
+                "population fell below floor, spawning green organisms"
+            );
+        }
+    }
+
+    pub fn memory_footprint(&self) -> MemoryFootprint {
+        let field = self.field.inner.capacity() * mem::size_of::<WorldCell>();
+        let organisms = self.count_alive() * mem::size_of::<Organism>();
+        let updates = self.updates.capacity() * mem::size_of::<usize>();
+        let cache = self.field.cache.memory_footprint();
+        let territory = self.field.territory.capacity() * mem::size_of::<Option<TerritoryClaim>>();
+        let shade = self.shade.capacity() * mem::size_of::<usize>();
+
+        MemoryFootprint {
+            field,
+            organisms,
+            updates,
+            cache,
+            territory,
+            shade,
+            total: field + organisms + updates + cache + territory + shade,
+        }
+    }
+
+    /// shuffles slice using world rng, so order is reproducible for seeded worlds
+    pub(crate) fn shuffle<T>(&mut self, slice: &mut [T]) {
+        slice.shuffle(self.rng.get_mut());
+    }
+
+    /// brings world back to its initial state with rng seeded by `seed`,
+    /// config and dimensions are kept
+    pub fn reseed(&mut self, seed: u64) {
+        self.field
+            .inner
+            .iter_mut()
+            .for_each(|cell| *cell = WorldCell::Empty);
+        self.field.territory = vec![None; self.field.inner.len()];
+        self.field.mutagen.clear();
+        self.updates.iter_mut().for_each(|update| *update = 0);
+        self.iteration = 1;
+        self.next_lineage = 1;
+        self.light_boost = (0, 0);
+        self.births.clear();
+        self.deaths.clear();
+        self.offspring_window.clear();
+        *self.rng.get_mut() = WorldRng::seed_from_u64(seed);
+        self.seed = seed;
+        tracing::info!(seed, "world rng reseeded");
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn snapshot(&self) -> WorldSnapshot {
+        WorldSnapshot {
+            config: self.config.clone(),
+            field: self.field.clone(),
+            iteration: self.iteration,
+            updates: self.updates.clone(),
+            next_lineage: self.next_lineage,
+        }
+    }
+
+    /// checks that field layers of snapshot agree on world size, so that restoring it
+    /// can not break indexing
+    pub fn validate_snapshot(snapshot: &WorldSnapshot) -> Result<(), String> {
+        let (len, width) = (snapshot.field.inner.len(), snapshot.field.get_width());
+        if width == 0 || len == 0 || len % width != 0 {
+            return Err(format!("{len} cells do not form rows of width {width}"));
+        }
+        if snapshot.updates.len() != len {
+            return Err(format!(
+                "{} update markers for {len} cells",
+                snapshot.updates.len()
+            ));
+        }
+        Ok(())
+    }
+
+    /// replaces world state with snapshot, behaviour functions of current config are kept
+    pub fn restore(&mut self, snapshot: WorldSnapshot) {
+        let mut config = snapshot.config;
+        config.reattach_behaviours(&self.config);
+
+        self.config = config;
+        self.width = snapshot.field.get_width();
+        self.field = snapshot.field;
+        self.iteration = snapshot.iteration;
+        self.updates = snapshot.updates;
+        self.next_lineage = snapshot.next_lineage;
+        self.births.clear();
+        self.deaths.clear();
+        self.offspring_window.clear();
+    }
+
     pub fn get_width(&self) -> usize {
         self.width
     }
@@ -154,6 +860,30 @@ impl World {
         self.field.inner.len() / self.width
     }
 
+    pub fn get_iteration(&self) -> usize {
+        self.iteration
+    }
+
+    pub fn count_alive(&self) -> usize {
+        self.field
+            .inner
+            .iter()
+            .filter(|cell| matches!(cell, WorldCell::Organism(..)))
+            .count()
+    }
+
+    /// removes all dead bodies, returns number of removed bodies
+    pub fn clear_dead(&mut self) -> usize {
+        let mut cleared = 0;
+        for cell in self.field.inner.iter_mut() {
+            if let WorldCell::DeadBody(..) = cell {
+                *cell = WorldCell::Empty;
+                cleared += 1;
+            }
+        }
+        cleared
+    }
+
     fn get_update(&self, (i, j): (usize, usize)) -> usize {
         self.updates[i * self.width + j]
     }
@@ -167,30 +897,89 @@ impl World {
         (i, j): (usize, usize),
         direction: Direction,
     ) -> Option<(usize, usize)> {
-        match direction {
-            Direction::Down if i == self.get_height() - 1 => {
-                return None;
-            }
+        let shift = direction.as_shift();
+        let i = wrap_axis(
+            i as isize + shift.0,
+            self.get_height(),
+            self.config.wrap_rows,
+        )?;
+        let j = wrap_axis(
+            j as isize + shift.1,
+            self.get_width(),
+            self.config.wrap_columns,
+        )?;
+        Some((i, j))
+    }
 
-            Direction::Up if i == 0 => {
-                return None;
-            }
+    /// signed shortest offset between two cells respecting world topology
+    fn shortest_offset(
+        &self,
+        (from_i, from_j): (usize, usize),
+        (to_i, to_j): (usize, usize),
+    ) -> (isize, isize) {
+        (
+            axis_offset(from_i, to_i, self.get_height(), self.config.wrap_rows),
+            axis_offset(from_j, to_j, self.get_width(), self.config.wrap_columns),
+        )
+    }
 
-            _ => {}
-        }
+    /// number of rows and columns separating two cells along the shortest path
+    pub fn toroidal_distance(&self, from: (usize, usize), to: (usize, usize)) -> (usize, usize) {
+        let (di, dj) = self.shortest_offset(from, to);
+        (di.unsigned_abs(), dj.unsigned_abs())
+    }
 
-        let shift = direction.as_shift();
-        let (i, j) = (i as isize + shift.0, j as isize + shift.1);
-        let (i, j) = (
-            i + self.get_height() as isize,
-            j + self.get_width() as isize,
-        );
-        let (i, j) = (
-            i as usize % self.get_height(),
-            j as usize % self.get_width(),
-        );
+    /// direction of the shortest path between two cells, vertical direction is preferred
+    /// on ties, None if both positions are the same
+    pub fn direction_toward(&self, from: (usize, usize), to: (usize, usize)) -> Option<Direction> {
+        let (di, dj) = self.shortest_offset(from, to);
+        offset_direction(di, dj)
+    }
 
-        Some((i, j))
+    /// direction with the most living organisms within `range` cells, every cell
+    /// is attributed to the direction of its dominant axis (vertical on ties),
+    /// None when there are no organisms around
+    pub fn crowd_direction(&self, (i, j): (usize, usize), range: usize) -> Option<Direction> {
+        // wrapping axes are limited so that no cell is counted twice
+        let axis_range = |length: usize, wraps: bool| -> isize {
+            if wraps {
+                range.min((length - 1) / 2) as isize
+            } else {
+                range as isize
+            }
+        };
+        let range_i = axis_range(self.get_height(), self.config.wrap_rows);
+        let range_j = axis_range(self.get_width(), self.config.wrap_columns);
+
+        let mut counts = [0usize; 4];
+        for di in -range_i..=range_i {
+            for dj in -range_j..=range_j {
+                let pos = wrap_axis(i as isize + di, self.get_height(), self.config.wrap_rows).zip(
+                    wrap_axis(j as isize + dj, self.get_width(), self.config.wrap_columns),
+                );
+                match (pos, offset_direction(di, dj)) {
+                    (Some(pos), Some(direction))
+                        if matches!(self.field[pos], WorldCell::Organism(..)) =>
+                    {
+                        counts[u8::from(direction) as usize] += 1;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Direction::ALL
+            .into_iter()
+            .zip(counts)
+            .filter(|(_, count)| *count > 0)
+            .fold(
+                None,
+                |best: Option<(Direction, usize)>, (direction, count)| match best {
+                    Some((_, best_count)) if best_count >= count => best,
+                    _ => Some((direction, count)),
+                },
+            )
+            .map(|(direction, _)| direction)
     }
 
     pub fn look_relative_mut(
@@ -211,125 +1000,664 @@ impl World {
         Some(&self.field[(i, j)])
     }
 
-    pub fn get_light(&self, i: usize) -> usize {
-        (self.config.light_behaviour)(i)
+    /// walks up to `range` cells in given direction stopping at first non-empty cell
+    /// or world edge, returns found cell together with walked distance
+    pub fn look_far(
+        &self,
+        (mut i, mut j): (usize, usize),
+        direction: Direction,
+        range: usize,
+    ) -> (Option<&WorldCell>, usize) {
+        let range = self.sight_limit(direction, range);
+        let mut cell = None;
+        for distance in 1..=range {
+            match self.relative_shift((i, j), direction) {
+                Some(pos) => {
+                    (i, j) = pos;
+                    cell = Some(&self.field[pos]);
+                    if !matches!(self.field[pos], WorldCell::Empty) {
+                        return (cell, distance);
+                    }
+                }
+                None => return (None, distance),
+            }
+        }
+        (cell, range)
     }
 
-    pub fn get_minerals(&self, i: usize) -> usize {
-        (self.config.minerals_behaviour)(i)
+    /// range of straight walk in given direction limited so that it does not
+    /// wrap around the world back onto the cell it started from
+    fn sight_limit(&self, direction: Direction, range: usize) -> usize {
+        let length = match direction {
+            Direction::Up | Direction::Down => self.get_height(),
+            Direction::Left | Direction::Right => self.get_width(),
+        };
+        range.min(length - 1)
     }
 
-    #[inline(always)]
-    fn run_bot_prelude(&mut self, (i, _j): (usize, usize), bot: &mut Organism) {
-        let minerals = self.get_minerals(i);
-        bot.add_minerals(minerals, self.config.max_minerals);
-        bot.age(&self.config.aging_mutation_freq);
+    /// number of consecutive empty cells in given direction, at most `range`
+    pub fn openness(
+        &self,
+        (mut i, mut j): (usize, usize),
+        direction: Direction,
+        range: usize,
+    ) -> usize {
+        let range = self.sight_limit(direction, range);
+        for distance in 0..range {
+            match self.relative_shift((i, j), direction) {
+                Some(pos) if matches!(self.field[pos], WorldCell::Empty) => (i, j) = pos,
+                _ => return distance,
+            }
+        }
+        range
     }
 
-    fn run_bot_action(
-        &mut self,
-        (i, j): (&mut usize, &mut usize),
-        bot: &mut Organism,
-    ) -> Result<(), ()> {
-        match bot.tick(self, (*i, *j)) {
-            Some(OrganismAction::TryEat(direction)) => {
-                let dead_energy = self.config.dead_energy;
-                let attack_cost = self.config.attack_cost;
-                match self.look_relative_mut((*i, *j), direction) {
-                    Some(&mut WorldCell::Organism(ref mut other))
-                        if bot.get_energy() > attack_cost =>
-                    {
-                        let energy = other.get_energy();
-
-                        let chance = mass_to_chance(bot.get_energy(), energy);
-                        bot.decrease_energy(attack_cost);
-                        if chance {
-                            bot.add_energy(energy.saturating_sub(dead_energy) / 2);
-                            *self.look_relative_mut((*i, *j), direction).unwrap() =
-                                WorldCell::Empty;
-                        } else {
-                            other.register_attack(direction.inverse());
-                        }
-                    }
-
-                    Some(cell @ &mut WorldCell::DeadBody(..)) => {
-                        let (energy, minerals) = match &cell {
-                            WorldCell::DeadBody(e, m) => (*e, *m),
-                            _ => unreachable!(),
-                        };
-                        *cell = WorldCell::Empty;
-                        bot.add_energy(energy / 2);
-                        bot.add_minerals(minerals / 2, self.config.max_minerals);
-                    }
-
-                    _any_other_case => {}
-                }
-            }
-            Some(OrganismAction::TryMove(direction)) => {
-                if let Some(WorldCell::Empty) = self.look_relative_mut((*i, *j), direction) {
-                    let (new_i, new_j) = self.relative_shift((*i, *j), direction).unwrap();
-                    *i = new_i;
-                    *j = new_j;
-                    *self.get_update_mut((*i, *j)) = self.get_update((*i, *j)).wrapping_add(1);
-                }
-            }
+    pub fn claim_territory(&mut self, (i, j): (usize, usize), lineage: usize) {
+        let len = self.field.inner.len();
+        if self.field.territory.len() != len {
+            self.field.territory.resize(len, None);
+        }
+        self.field.territory[i * self.width + j] = Some(TerritoryClaim {
+            lineage,
+            claimed_at: self.iteration,
+        });
+    }
 
-            Some(OrganismAction::Die) => {
-                return Err(());
-            }
+    /// lineage owning the cell, claims expire after `territory_lifetime` ticks
+    pub fn territory_owner(&self, (i, j): (usize, usize)) -> Option<usize> {
+        let claim = (*self.field.territory.get(i * self.width + j)?)?;
+        if self.iteration.saturating_sub(claim.claimed_at) < self.config.territory_lifetime {
+            Some(claim.lineage)
+        } else {
+            None
+        }
+    }
 
-            Some(OrganismAction::TryClone(child_size, child_minerals, direction)) => {
-                if let Some(WorldCell::Empty) = self.look_relative_mut((*i, *j), direction) {
-                    if let Some(child) = bot.split_off(
-                        || self.field.cache.get_alloc(),
-                        child_size,
-                        child_minerals,
-                        self.config.mutation_chance,
-                    ) {
-                        let pos = self.relative_shift((*i, *j), direction).unwrap();
-                        self.field[pos] = WorldCell::Organism(child);
-                        return Ok(());
-                    }
+    /// sets mutation multiplier of every cell within `radius` of `center`
+    pub fn paint_mutagen(&mut self, center: (usize, usize), radius: usize, multiplier: usize) {
+        let len = self.field.inner.len();
+        if self.field.mutagen.len() != len {
+            self.field.mutagen = vec![1; len];
+        }
+        for i in 0..self.get_height() {
+            for j in 0..self.get_width() {
+                let (di, dj) = self.toroidal_distance(center, (i, j));
+                if di * di + dj * dj <= radius.saturating_mul(radius) {
+                    self.field.mutagen[i * self.width + j] = multiplier;
                 }
             }
+        }
+    }
 
-            Some(OrganismAction::ShareEnergy(amount, direction)) => {
-                if let Some(WorldCell::Organism(ref mut o)) =
-                    self.look_relative_mut((*i, *j), direction)
-                {
-                    o.add_energy(amount)
-                }
-            }
+    /// per-mille mutation chance of organisms cloning at given cell
+    fn mutation_chance_at(&self, (i, j): (usize, usize)) -> usize {
+        let multiplier = self
+            .field
+            .mutagen
+            .get(i * self.width + j)
+            .copied()
+            .unwrap_or(1);
+        self.config
+            .mutation_chance
+            .saturating_mul(multiplier)
+            .min(1000)
+    }
 
-            Some(OrganismAction::ShareMinerals(amount, direction)) => {
-                let max_minerals = self.config.max_minerals;
-                if let Some(WorldCell::Organism(ref mut o)) =
-                    self.look_relative_mut((*i, *j), direction)
-                {
-                    o.add_minerals(amount, max_minerals)
-                }
-            }
+    /// adds extra light to every row for given number of ticks
+    pub fn boost_light(&mut self, amount: usize, duration: usize) {
+        self.light_boost = (amount, self.iteration.saturating_add(duration));
+    }
 
-            None => {}
+    pub fn get_light(&self, i: usize) -> usize {
+        let (boost, until) = self.light_boost;
+        let light =
+            (self.config.light_behaviour)(i) + if self.iteration < until { boost } else { 0 };
+        if self.is_night() {
+            light * self.config.night_light_percent / 100
+        } else {
+            light
         }
-        Ok(())
     }
 
-    #[inline(always)]
-    fn run_bot_postlude(&mut self, (_i, _j): (usize, usize), bot: &mut Organism) {
-        // 1 is already subtracted via action
-        bot.decrease_energy(energy_soft_cap(bot.get_energy(), self.config.max_cell_size));
+    /// light available at given cell after occlusion by organisms above it
+    pub fn effective_light(&self, (i, j): (usize, usize)) -> usize {
+        let idx = i * self.width + j;
+        let shade = self.shade.get(idx).copied().unwrap_or(0);
+        let glow = self.glow.1.get(idx).copied().unwrap_or(0);
+        self.get_light(i).saturating_sub(shade) + glow
     }
 
-    #[inline(always)]
-    fn process_bot(&mut self, (mut i, mut j): (usize, usize), mut bot: Box<Organism>) {
-        self.run_bot_prelude((i, j), bot.as_mut());
+    fn emit_glow(&mut self, (i, j): (usize, usize)) {
+        let len = self.field.inner.len();
+        if self.glow.0.len() != len {
+            self.glow.0.resize(len, 0);
+        }
+        let neighbours = Direction::ALL.map(|direction| self.relative_shift((i, j), direction));
+        for (cell_i, cell_j) in neighbours.into_iter().flatten().chain([(i, j)]) {
+            self.glow.0[cell_i * self.width + cell_j] += self.config.glow_light;
+        }
+    }
+
+    fn update_shade(&mut self) {
+        let per_energy = self.config.occlusion_per_energy;
+        let len = self.field.inner.len();
+        if self.shade.len() != len {
+            self.shade.resize(len, 0);
+        }
+        if per_energy == 0 {
+            self.shade.iter_mut().for_each(|shade| *shade = 0);
+            return;
+        }
+
+        for j in 0..self.get_width() {
+            let mut energy_above = 0;
+            for i in 0..self.get_height() {
+                self.shade[i * self.width + j] = energy_above * per_energy / 100;
+                if let WorldCell::Organism(o) = &self.field[(i, j)] {
+                    energy_above += o.get_energy();
+                }
+            }
+        }
+    }
+
+    /// position inside of day/night cycle, None if cycle is disabled
+    pub fn day_phase(&self) -> Option<usize> {
+        self.iteration.checked_rem(self.config.day_length)
+    }
+
+    /// second half of every cycle is night
+    pub fn is_night(&self) -> bool {
+        self.day_phase()
+            .is_some_and(|phase| phase >= self.config.day_length / 2)
+    }
+
+    pub fn get_minerals(&self, i: usize) -> usize {
+        (self.config.minerals_behaviour)(self.get_height().saturating_sub(i + 1))
+    }
+
+    #[inline(always)]
+    fn run_bot_prelude(&mut self, (i, j): (usize, usize), bot: &mut Organism) {
+        let minerals = self.get_minerals(i);
+        bot.add_minerals(minerals, self.config.mineral_capacity(bot.get_energy()));
+        bot.age(&self.config.aging_mutation_freq, self.rng.get_mut());
+        let energy = bot.get_energy();
+        bot.decrease_energy(bot.get_age() * self.config.senescence_rate / 1000);
+        // gene spans the whole height, whatever size the world has
+        let preferred_row = bot.get_preferred_depth() as usize * self.get_height() / 256;
+        let mismatch = i.abs_diff(preferred_row);
+        bot.decrease_energy(mismatch * self.config.depth_mismatch_penalty / 100);
+        bot.decrease_energy(bot.get_complexity() * self.config.complexity_cost / 100);
+        self.track_energy(|flow| flow.decayed += energy - bot.get_energy());
+        bot.decay_armor(self.config.armor_decay);
+        bot.digest();
+        self.apply_edge_flux(i, bot);
+        if self.config.allow_multicell {
+            self.share_with_parent((i, j), bot);
+        }
+    }
+
+    fn apply_edge_flux(&self, i: usize, bot: &mut Organism) {
+        if self.config.wrap_rows {
+            return;
+        }
+        let flux = self.config.edge_flux;
+        let (energy, minerals) = if i == 0 {
+            (flux.top_energy, flux.top_minerals)
+        } else if i == self.get_height() - 1 {
+            (flux.bottom_energy, flux.bottom_minerals)
+        } else {
+            return;
+        };
+
+        if energy >= 0 {
+            bot.add_energy(energy.unsigned_abs());
+        } else {
+            bot.decrease_energy(energy.unsigned_abs());
+        }
+        if minerals >= 0 {
+            let capacity = self.config.mineral_capacity(bot.get_energy());
+            bot.add_minerals(minerals.unsigned_abs(), capacity);
+        } else {
+            bot.decrease_minerals(minerals.unsigned_abs());
+        }
+    }
+
+    /// equalizes part of energy difference between linked child and its parent,
+    /// link is dropped once parent is no longer found in linked direction
+    fn share_with_parent(&mut self, (i, j): (usize, usize), bot: &mut Organism) {
+        let direction = match bot.get_parent_link() {
+            Some(direction) => direction,
+            None => return,
+        };
+        let rate = self.config.link_share_rate;
+
+        match self.look_relative_mut((i, j), direction) {
+            Some(WorldCell::Organism(parent)) => {
+                let (parent_energy, own_energy) = (parent.get_energy(), bot.get_energy());
+                if parent_energy > own_energy {
+                    let amount = (parent_energy - own_energy) / 2 * rate / 100;
+                    parent.decrease_energy(amount);
+                    bot.add_energy(amount);
+                } else {
+                    let amount = (own_energy - parent_energy) / 2 * rate / 100;
+                    bot.decrease_energy(amount);
+                    parent.add_energy(amount);
+                }
+            }
+            _ => bot.set_parent_link(None),
+        }
+    }
+
+    /// splits energy evenly between adjacent living kin of dying organism,
+    /// returns energy left for the corpse (everything if there are no heirs)
+    fn bequeath(&mut self, (i, j): (usize, usize), bot: &Organism, energy: usize) -> usize {
+        let threshold = self.config.kin_genetic_threshold;
+        let heirs: Vec<(usize, usize)> = Direction::ALL
+            .iter()
+            .filter_map(|&direction| self.relative_shift((i, j), direction))
+            .filter(|&pos| matches!(&self.field[pos], WorldCell::Organism(o) if o.is_kin(bot, threshold)))
+            .collect();
+
+        if heirs.is_empty() {
+            return energy;
+        }
+
+        let share = energy / heirs.len();
+        for pos in &heirs {
+            if let WorldCell::Organism(heir) = &mut self.field[*pos] {
+                heir.add_energy(share);
+            }
+        }
+        energy - share * heirs.len()
+    }
+
+    /// distributes all energy and minerals of organism according to
+    /// `self_destruct_mode`, organism's own cell is left empty or holds a corpse
+    fn self_destruct(&mut self, (i, j): (usize, usize), bot: &Organism) {
+        let mode = self.config.self_destruct_mode;
+        let receivers: Vec<(usize, usize)> = Direction::ALL
+            .iter()
+            .filter_map(|&direction| self.relative_shift((i, j), direction))
+            .filter(|&pos| {
+                matches!(
+                    (&self.field[pos], mode),
+                    (WorldCell::Organism(..), SelfDestructMode::Feed)
+                        | (WorldCell::Empty, SelfDestructMode::Scatter)
+                )
+            })
+            .collect();
+
+        // own cell receives a share only when scattering
+        let shares = receivers.len() + (mode == SelfDestructMode::Scatter) as usize;
+        let (energy, minerals) = (bot.get_energy(), bot.get_minerals());
+        let (energy_share, minerals_share) = match shares {
+            0 => (0, 0),
+            shares => (energy / shares, minerals / shares),
+        };
+
+        for &pos in &receivers {
+            match &mut self.field[pos] {
+                WorldCell::Organism(receiver) => {
+                    receiver.add_energy(energy_share);
+                    let capacity = self.config.mineral_capacity(receiver.get_energy());
+                    receiver.add_minerals(minerals_share, capacity);
+                }
+                cell => *cell = WorldCell::DeadBody(energy_share, minerals_share, self.iteration),
+            }
+        }
+
+        let (energy_left, minerals_left) = (
+            energy - energy_share * receivers.len(),
+            minerals - minerals_share * receivers.len(),
+        );
+        self.field[(i, j)] = if energy_left > 0 || minerals_left > 0 {
+            WorldCell::DeadBody(energy_left, minerals_left, self.iteration)
+        } else {
+            WorldCell::Empty
+        };
+    }
+
+    /// program of first living neighbour not located in direction of the child
+    fn find_partner(&self, (i, j): (usize, usize), child_direction: Direction) -> Option<Program> {
+        [
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+        ]
+        .into_iter()
+        .filter(|direction| *direction != child_direction)
+        .find_map(|direction| match self.look_relative((i, j), direction) {
+            Some(WorldCell::Organism(o)) => Some(*o.get_program()),
+            _ => None,
+        })
+    }
+
+    /// number of cells organism may travel with single move
+    fn move_distance(&self, energy: usize) -> usize {
+        match energy.checked_div(self.config.energy_speed_scaling) {
+            Some(extra) => (1 + extra).min(self.config.max_speed.max(1)),
+            None => 1,
+        }
+    }
+
+    fn run_bot_action(
+        &mut self,
+        (i, j): (&mut usize, &mut usize),
+        bot: &mut Organism,
+        action: Option<OrganismAction>,
+    ) -> Result<(), Death> {
+        match action {
+            Some(OrganismAction::TryEat(_)) if bot.is_digesting() => {}
+
+            Some(OrganismAction::TryEat(direction)) => {
+                let dead_energy = self.config.dead_energy;
+                let attack_cost = self.config.attack_cost;
+                let deterministic_combat = self.config.deterministic_combat;
+                let kin_safe_eating = self.config.kin_safe_eating;
+                let kin_threshold = self.config.kin_genetic_threshold;
+                let target = self.relative_shift((*i, *j), direction);
+                match target.map(|pos| &mut self.field[pos]) {
+                    Some(&mut WorldCell::Organism(ref other))
+                        if kin_safe_eating && bot.is_kin(other, kin_threshold) => {}
+
+                    Some(&mut WorldCell::Organism(ref mut other))
+                        if bot.get_energy() > attack_cost =>
+                    {
+                        let energy = other.get_energy();
+
+                        let chance = if deterministic_combat {
+                            bot.get_energy() > energy
+                        } else {
+                            mass_to_chance(
+                                bot.get_energy(),
+                                bot.get_attack_power(),
+                                energy,
+                                other.get_armor(),
+                                self.rng.get_mut(),
+                            )
+                        };
+                        bot.decrease_energy(attack_cost);
+                        if chance {
+                            let offspring_count = other.get_offspring_count();
+                            bot.remember_prey(other);
+                            bot.start_digestion(self.config.digestion_ticks);
+                            let gained = bot.metabolize(energy.saturating_sub(dead_energy) / 2);
+                            bot.add_energy(gained);
+                            self.track_energy(|flow| flow.eaten += gained);
+                            self.field[target.unwrap()] = WorldCell::Empty;
+                            self.record_death(target.unwrap(), offspring_count);
+                        } else {
+                            other.register_attack(direction.inverse());
+                        }
+                    }
+
+                    Some(cell @ &mut WorldCell::DeadBody(..)) => {
+                        let (energy, minerals) = match &cell {
+                            WorldCell::DeadBody(e, m, _) => (*e, *m),
+                            _ => unreachable!(),
+                        };
+                        *cell = WorldCell::Empty;
+                        bot.start_digestion(self.config.digestion_ticks);
+                        let gained = bot.metabolize(energy / 2);
+                        bot.add_energy(gained);
+                        self.track_energy(|flow| flow.eaten += gained);
+                        let capacity = self.config.mineral_capacity(bot.get_energy());
+                        bot.add_minerals(minerals / 2, capacity);
+                    }
+
+                    None if self.config.blocked_action_feedback => bot.register_blocked(),
 
-        match self.run_bot_action((&mut i, &mut j), bot.as_mut()) {
+                    _any_other_case => {}
+                }
+            }
+            Some(OrganismAction::TryGraze(direction)) => {
+                bot.decrease_energy(self.config.graze_cost);
+                let amount = self.config.graze_amount;
+                let blocked_feedback = self.config.blocked_action_feedback;
+                match self.look_relative_mut((*i, *j), direction) {
+                    Some(WorldCell::Organism(other)) => {
+                        let taken = amount.min(other.get_energy());
+                        other.decrease_energy(taken);
+                        let gained = bot.metabolize(taken);
+                        bot.add_energy(gained);
+                        self.track_energy(|flow| flow.eaten += gained);
+                    }
+                    None if blocked_feedback => bot.register_blocked(),
+                    _ => {}
+                }
+            }
+            Some(OrganismAction::TryFuse(_)) if !self.config.allow_fusion => {}
+
+            Some(OrganismAction::Glow) if !self.config.allow_bioluminescence => {}
+
+            Some(OrganismAction::Glow) => {
+                bot.decrease_energy(self.config.glow_cost);
+                self.emit_glow((*i, *j));
+            }
+
+            Some(OrganismAction::TryFuse(direction)) => {
+                let kin_threshold = self.config.kin_genetic_threshold;
+                if let Some(pos) = self.relative_shift((*i, *j), direction) {
+                    let fusable = match &self.field[pos] {
+                        WorldCell::Organism(other) => bot.is_kin(other, kin_threshold),
+                        _ => false,
+                    };
+                    // initiator keeps its program and absorbs resources of the neighbour up to
+                    // `max_cell_size` and mineral capacity, the rest is lost with the freed cell.
+                    // Allocation of the neighbour goes back to the cache
+                    if fusable {
+                        if let WorldCell::Organism(other) =
+                            mem::replace(&mut self.field[pos], WorldCell::Empty)
+                        {
+                            let room = self.config.max_cell_size.saturating_sub(bot.get_energy());
+                            bot.add_energy(other.get_energy().min(room));
+                            let capacity = self.config.mineral_capacity(bot.get_energy());
+                            bot.add_minerals(other.get_minerals(), capacity);
+                            self.field.cache.store_drop(other);
+                        }
+                    }
+                }
+            }
+            Some(OrganismAction::TryMove(direction)) => {
+                let blocked_feedback = self.config.blocked_action_feedback;
+                let mut moved = false;
+                for _ in 0..self.move_distance(bot.get_energy()) {
+                    match self.look_relative_mut((*i, *j), direction) {
+                        Some(WorldCell::Empty) => {
+                            let (new_i, new_j) = self.relative_shift((*i, *j), direction).unwrap();
+                            *i = new_i;
+                            *j = new_j;
+                            moved = true;
+                        }
+                        None if blocked_feedback && !moved => {
+                            bot.register_blocked();
+                            break;
+                        }
+                        _ => break,
+                    }
+                }
+                if moved {
+                    bot.record_move(direction);
+                    bot.set_parent_link(None);
+                    // destination is marked as processed so organism is not run again
+                    // this tick, assigning (not incrementing) keeps cells behind the sweep
+                    // from getting ahead of iteration and being skipped next tick
+                    *self.get_update_mut((*i, *j)) = self.iteration;
+                }
+            }
+
+            Some(OrganismAction::Die) => {
+                return Err(Death::Natural);
+            }
+
+            Some(OrganismAction::SelfDestruct) => {
+                self.self_destruct((*i, *j), bot);
+                return Err(Death::Burst);
+            }
+
+            Some(OrganismAction::TryClone(..))
+                if bot.get_energy() < self.config.min_clone_parent_energy => {}
+
+            Some(OrganismAction::TryClone(child_size, child_minerals, direction)) => {
+                if let Some(WorldCell::Empty) = self.look_relative_mut((*i, *j), direction) {
+                    let partner = if self.config.sexual_reproduction {
+                        self.find_partner((*i, *j), direction)
+                    } else {
+                        None
+                    };
+
+                    // clutch shares energy and minerals of a single child, first child goes
+                    // in requested direction and the rest occupy free cells clockwise from it
+                    let clutch_size = self.config.clutch_size.max(1);
+                    let (child_size, child_minerals) =
+                        (child_size / clutch_size, child_minerals / clutch_size);
+
+                    let mutation_chance = self.mutation_chance_at((*i, *j));
+                    let mut child_direction = direction;
+                    let mut placed = 0;
+                    for _ in 0..Direction::ALL.len() {
+                        if placed == clutch_size {
+                            break;
+                        }
+                        let pos = match self.relative_shift((*i, *j), child_direction) {
+                            Some(pos) if matches!(self.field[pos], WorldCell::Empty) => pos,
+                            _ => {
+                                child_direction = child_direction.next_clockwise();
+                                continue;
+                            }
+                        };
+
+                        let mut child = match bot.split_off(
+                            || self.field.cache.get_alloc(),
+                            child_size,
+                            child_minerals,
+                            Mutation {
+                                chance: mutation_chance,
+                                kind: self.config.mutation_kind,
+                            },
+                            partner.as_ref(),
+                            self.rng.get_mut(),
+                        ) {
+                            Some(child) => child,
+                            None => break,
+                        };
+                        if self.config.allow_multicell {
+                            child.set_parent_link(Some(child_direction.inverse()));
+                        }
+                        if self.config.inherit_home {
+                            child.set_home(bot.get_home());
+                        }
+                        self.field[pos] = WorldCell::Organism(child);
+                        self.record_birth(pos);
+                        placed += 1;
+                        child_direction = child_direction.next_clockwise();
+                    }
+                }
+            }
+
+            Some(OrganismAction::ClaimTerritory) => {
+                self.claim_territory((*i, *j), bot.get_lineage());
+            }
+
+            Some(OrganismAction::ShareEnergy(amount, direction)) => {
+                if let Some(WorldCell::Organism(ref mut o)) =
+                    self.look_relative_mut((*i, *j), direction)
+                {
+                    o.add_energy(amount);
+                    self.track_energy(|flow| flow.shared += amount);
+                }
+            }
+
+            Some(OrganismAction::ShareMinerals(amount, direction)) => {
+                if let Some(pos) = self.relative_shift((*i, *j), direction) {
+                    if let WorldCell::Organism(ref mut o) = self.field[pos] {
+                        let capacity = self.config.mineral_capacity(o.get_energy());
+                        o.add_minerals(amount, capacity)
+                    }
+                }
+            }
+
+            None => {}
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn run_bot_postlude(&mut self, (_i, _j): (usize, usize), bot: &mut Organism) {
+        // 1 is already subtracted via action
+        let energy = bot.get_energy();
+        bot.decrease_energy(
+            bot.metabolize(energy_soft_cap(bot.get_energy(), self.config.max_cell_size)),
+        );
+        self.track_energy(|flow| flow.decayed += energy - bot.get_energy());
+    }
+
+    #[inline(always)]
+    fn process_bot(&mut self, (i, j): (usize, usize), mut bot: Box<Organism>) {
+        if bot.is_dormant() {
+            if self.effective_light((i, j)) >= self.config.germination_light {
+                bot.germinate();
+            }
+            self.field[(i, j)] = WorldCell::Organism(bot);
+            return;
+        }
+
+        self.run_bot_prelude((i, j), bot.as_mut());
+
+        let actions = bot.tick_actions(
+            self,
+            (i, j),
+            self.config.actions_per_tick,
+            &mut *self.rng.lock(),
+        );
+        self.apply_bot_actions((i, j), bot, actions);
+    }
+
+    /// applies actions decided by organism, then splits it if it has grown enough
+    /// and puts it back into the field unless it died
+    fn apply_bot_actions(
+        &mut self,
+        (mut i, mut j): (usize, usize),
+        mut bot: Box<Organism>,
+        actions: Actions,
+    ) {
+        if actions.is_empty() {
+            if let Some(hook) = self.hooks.after_action.as_mut() {
+                hook((i, j), None);
+            }
+        }
+
+        let mut outcome = Ok(());
+        for action in actions {
+            let origin = (i, j);
+            outcome = self.run_bot_action((&mut i, &mut j), bot.as_mut(), Some(action));
+            if let Some(hook) = self.hooks.after_action.as_mut() {
+                hook(origin, Some(action));
+            }
+            if outcome.is_err() {
+                break;
+            }
+        }
+
+        match outcome {
             Ok(_) => {}
-            Err(_) => {
+            Err(Death::Burst) => {
+                self.record_death((i, j), bot.get_offspring_count());
+                self.field.cache.store_drop(bot);
+                return;
+            }
+            Err(Death::Natural) => {
+                self.record_death((i, j), bot.get_offspring_count());
+                // organism dies of exhaustion, so energy of its body is what kin inherit
+                let body_energy = self.config.dead_energy + bot.get_energy();
+                let corpse_energy = if self.config.death_inheritance {
+                    self.bequeath((i, j), &bot, body_energy)
+                } else {
+                    body_energy
+                };
                 self.field[(i, j)] =
-                    WorldCell::DeadBody(self.config.dead_energy, bot.get_minerals());
+                    WorldCell::DeadBody(corpse_energy, bot.get_minerals(), self.iteration);
 
                 self.field.cache.store_drop(bot);
 
@@ -337,23 +1665,31 @@ impl World {
             }
         }
 
-        let child = if let (Ok((child_size, child_minerals)), false) = (
-            (self.config.split_behaviour)(bot.get_energy(), bot.get_minerals()),
-            bot.can_clone,
-        ) {
-            bot.split_off(
+        // free cell is looked up before splitting, so energy and minerals
+        // stay with the parent when the child would have nowhere to go
+        let split = match (self.config.split_behaviour)(bot.get_energy(), bot.get_minerals()) {
+            Ok(sizes) if !bot.can_clone => self.find_free_neighbour((i, j)).map(|pos| (pos, sizes)),
+            _ => None,
+        };
+
+        if let Some((pos, (child_size, child_minerals))) = split {
+            let mutation_chance = self.mutation_chance_at((i, j));
+            if let Some(mut child) = bot.split_off(
                 || self.field.cache.get_alloc(),
                 child_size,
                 child_minerals,
-                self.config.mutation_chance,
-            )
-        } else {
-            None
-        };
-
-        if let Some(child) = child {
-            if let Err(bot) = self.try_place_bot((i, j), child) {
-                self.field.cache.store_drop(bot);
+                Mutation {
+                    chance: mutation_chance,
+                    kind: self.config.mutation_kind,
+                },
+                None,
+                self.rng.get_mut(),
+            ) {
+                if self.config.inherit_home {
+                    child.set_home(bot.get_home());
+                }
+                self.field[pos] = WorldCell::Organism(child);
+                self.record_birth(pos);
             }
         }
 
@@ -362,53 +1698,176 @@ impl World {
         self.field[(i, j)] = WorldCell::Organism(bot);
     }
 
+    /// empty cell adjacent to given one, neighbours are tried in random order
     #[inline]
-    fn try_place_bot(
-        &mut self,
-        (i, j): (usize, usize),
-        bot: Box<Organism>,
-    ) -> Result<(), Box<Organism>> {
+    fn find_free_neighbour(&mut self, (i, j): (usize, usize)) -> Option<(usize, usize)> {
         let mut directions = [
             Direction::Up,
             Direction::Down,
             Direction::Left,
             Direction::Right,
         ];
-        directions.shuffle(&mut thread_rng());
-        for direction in directions {
-            if let Some(WorldCell::Empty) = self.look_relative_mut((i, j), direction) {
-                let pos = self.relative_shift((i, j), direction).unwrap();
-                self.field[pos] = WorldCell::Organism(bot);
-                return Ok(());
+        self.shuffle(&mut directions);
+        directions
+            .into_iter()
+            .find(|&direction| {
+                matches!(
+                    self.look_relative((i, j), direction),
+                    Some(WorldCell::Empty)
+                )
+            })
+            .and_then(|direction| self.relative_shift((i, j), direction))
+    }
+
+    fn process_cell(&mut self, (i, j): (usize, usize)) {
+        let mut possible_bot = WorldCell::Empty;
+        mem::swap(&mut self.field[(i, j)], &mut possible_bot);
+
+        match possible_bot {
+            WorldCell::Organism(o) => {
+                self.process_bot((i, j), o);
+            }
+            b @ WorldCell::DeadBody(..) => {
+                self.field[(i, j)] = b;
             }
+            _ => {}
         }
-        Err(bot)
     }
 
-    pub fn tick(&mut self) {
+    /// runs a single organism for one tick leaving the rest of the world frozen,
+    /// fails if there is no living organism at given position
+    pub fn step_cell(&mut self, (i, j): (usize, usize)) -> Result<(), ()> {
+        if i >= self.get_height() || j >= self.get_width() {
+            return Err(());
+        }
+        if !matches!(self.field[(i, j)], WorldCell::Organism(..)) {
+            return Err(());
+        }
+        self.update_shade();
+        self.process_cell((i, j));
+        Ok(())
+    }
+
+    /// organisms part of [World::tick] when `parallel_tick` is enabled
+    fn tick_organisms_parallel(&mut self) {
+        // prelude draws from world rng and touches linked neighbours,
+        // so it is run for everyone before any decision is made
+        let mut active = vec![false; self.field.inner.len()];
         for i in 0..self.get_height() {
             for j in 0..self.get_width() {
                 if self.get_update((i, j)) == self.iteration {
                     continue;
                 }
-
-                let mut possible_bot = WorldCell::Empty;
-                mem::swap(&mut self.field[(i, j)], &mut possible_bot);
-
-                match possible_bot {
-                    WorldCell::Organism(o) => {
-                        self.process_bot((i, j), o);
+                let mut bot = match mem::replace(&mut self.field[(i, j)], WorldCell::Empty) {
+                    WorldCell::Organism(bot) => bot,
+                    other => {
+                        self.field[(i, j)] = other;
+                        continue;
                     }
-                    b @ WorldCell::DeadBody(..) => {
-                        self.field[(i, j)] = b;
+                };
+                if !bot.is_dormant() {
+                    self.run_bot_prelude((i, j), bot.as_mut());
+                    active[i * self.width + j] = true;
+                } else if self.effective_light((i, j)) >= self.config.germination_light {
+                    bot.germinate();
+                }
+                self.field[(i, j)] = WorldCell::Organism(bot);
+            }
+        }
+
+        // every row is planned by a single thread with its own rng derived from row
+        // index, so decisions do not depend on the order threads happen to run them in
+        let seed: u64 = self.rng.get_mut().gen();
+        let world = &*self;
+        let plans: Vec<Plan> = (0..world.get_height())
+            .into_par_iter()
+            .flat_map_iter(|i| {
+                let active = &active;
+                let mut rng = WorldRng::seed_from_u64(seed ^ i as u64);
+                (0..world.get_width()).filter_map(move |j| {
+                    let idx = i * world.width + j;
+                    match &world.field[(i, j)] {
+                        WorldCell::Organism(bot) if active[idx] => {
+                            let mut planned = **bot;
+                            let actions = planned.tick_actions(
+                                world,
+                                (i, j),
+                                world.config.actions_per_tick,
+                                &mut rng,
+                            );
+                            Some(Plan {
+                                pos: (i, j),
+                                before: **bot,
+                                planned,
+                                actions,
+                            })
+                        }
+                        _ => None,
                     }
-                    _ => {}
+                })
+            })
+            .collect();
+
+        // organisms only get into other cells by moving, which marks the destination
+        // as updated, or by being born there, so together those tell whether organism
+        // in planned cell is still the one that made the plan
+        self.newborn_cells = vec![false; self.field.inner.len()];
+        for plan in plans {
+            let (i, j) = plan.pos;
+            if self.get_update((i, j)) == self.iteration || self.newborn_cells[i * self.width + j] {
+                continue;
+            }
+            // planned organism may have been eaten or fused by the time its turn comes
+            let mut bot = match mem::replace(&mut self.field[(i, j)], WorldCell::Empty) {
+                WorldCell::Organism(bot) => bot,
+                other => {
+                    self.field[(i, j)] = other;
+                    continue;
                 }
+            };
+            let current = *bot;
+            *bot = plan.planned;
+            bot.carry_external_changes(&plan.before, &current);
+            self.apply_bot_actions((i, j), bot, plan.actions);
+
+            *self.get_update_mut((i, j)) = self.iteration;
+        }
+        self.newborn_cells = vec![];
+    }
 
-                *self.get_update_mut((i, j)) = self.get_update((i, j)).wrapping_add(1);
+    pub fn tick(&mut self) {
+        if let Some(hook) = self.hooks.before_tick.as_mut() {
+            hook(self.iteration);
+        }
+        self.births.decay(self.config.heatmap_retain);
+        self.deaths.decay(self.config.heatmap_retain);
+        *self.energy_flow.0.get_mut() = EnergyFlow::default();
+        self.update_shade();
+        if self.config.parallel_tick {
+            self.tick_organisms_parallel();
+        } else {
+            for i in 0..self.get_height() {
+                for j in 0..self.get_width() {
+                    if self.get_update((i, j)) == self.iteration {
+                        continue;
+                    }
+
+                    self.process_cell((i, j));
+
+                    *self.get_update_mut((i, j)) = self.iteration;
+                }
             }
         }
 
+        self.enforce_population_floor();
+        self.energy_flow.1 = *self.energy_flow.0.get_mut();
+        // glow becomes visible only to the next tick, so it does not depend on scan order
+        std::mem::swap(&mut self.glow.0, &mut self.glow.1);
+        self.glow.0.iter_mut().for_each(|glow| *glow = 0);
+
+        if let Some(hook) = self.hooks.after_tick.as_mut() {
+            hook(self.iteration);
+        }
         self.iteration = self.iteration.wrapping_add(1);
         self.measure_steps += 1;
     }
@@ -440,13 +1899,1330 @@ impl Display for World {
     }
 }
 
-/// computate chance of eating based on masses of two cells
+/// coordinate moved to the other side of the world if axis wraps,
+/// None if it falls outside of bounded axis
 #[inline(always)]
-fn mass_to_chance(own_mass: usize, target_mass: usize) -> bool {
-    thread_rng().gen_ratio(own_mass as u32, (own_mass + target_mass + 1) as u32)
+fn wrap_axis(coordinate: isize, length: usize, wraps: bool) -> Option<usize> {
+    let length = length as isize;
+    if wraps {
+        Some(coordinate.rem_euclid(length) as usize)
+    } else if (0..length).contains(&coordinate) {
+        Some(coordinate as usize)
+    } else {
+        None
+    }
+}
+
+/// direction of dominant axis of an offset, vertical on ties, None for zero offset
+fn offset_direction(di: isize, dj: isize) -> Option<Direction> {
+    if di == 0 && dj == 0 {
+        None
+    } else if di.abs() >= dj.abs() {
+        Some(if di > 0 {
+            Direction::Down
+        } else {
+            Direction::Up
+        })
+    } else {
+        Some(if dj > 0 {
+            Direction::Right
+        } else {
+            Direction::Left
+        })
+    }
+}
+
+/// signed offset from `from` to `to`, going across the edge when that is shorter
+fn axis_offset(from: usize, to: usize, length: usize, wraps: bool) -> isize {
+    let delta = to as isize - from as isize;
+    if !wraps {
+        return delta;
+    }
+    let length = length as isize;
+    let delta = delta.rem_euclid(length);
+    if delta > length / 2 {
+        delta - length
+    } else {
+        delta
+    }
+}
+
+/// computate chance of eating based on masses of two cells,
+/// attack power (0..=255) raises own mass up to twice,
+/// target armor (0..=255) scales chance down proportionally
+#[inline(always)]
+fn mass_to_chance<R: Rng + ?Sized>(
+    own_mass: usize,
+    attack_power: usize,
+    target_mass: usize,
+    target_armor: usize,
+    rng: &mut R,
+) -> bool {
+    let armor = target_armor.min(255) as f64;
+    let own_mass = own_mass as f64 * (256 + attack_power.min(255)) as f64 / 256.0;
+    // computed in floating point, so that huge masses can not overflow into invalid ratio
+    let chance = own_mass * (256.0 - armor) / ((own_mass + target_mass as f64 + 1.0) * 256.0);
+    rng.gen_bool(chance.clamp(0.0, 1.0))
 }
 
 #[inline(always)]
 fn energy_soft_cap(mass: usize, cap: usize) -> usize {
     (mass as f64 / cap as f64).ceil() as usize
 }
+
+#[cfg(test)]
+mod test {
+    use std::{
+        mem,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    use rand::{distributions::Bernoulli, thread_rng};
+
+    use super::{
+        energy_soft_cap, mass_to_chance, EdgeFlux, PlacementOrder, SelfDestructMode, World,
+        WorldCell, WorldConfig,
+    };
+    use crate::cells::{
+        code::{OpCode, Program, CODE_SIZE},
+        disasters::Disaster,
+        organism::{Direction, Organism},
+    };
+
+    fn single_op_organism(energy: usize, op: OpCode) -> Box<Organism> {
+        Box::new(Organism::with_program(
+            energy,
+            0,
+            Program {
+                code: [op; CODE_SIZE],
+            },
+        ))
+    }
+
+    fn stable_config() -> WorldConfig {
+        WorldConfig {
+            aging_mutation_freq: Bernoulli::new(0.0).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    fn get_organism(world: &World, pos: (usize, usize)) -> &Organism {
+        match &world.field[pos] {
+            WorldCell::Organism(o) => o,
+            _ => panic!("expected organism at {:?}", pos),
+        }
+    }
+
+    #[test]
+    fn test_armor_reduces_eat_chance() {
+        let trials = 10_000;
+        let unarmored = (0..trials)
+            .filter(|_| mass_to_chance(100, 0, 100, 0, &mut thread_rng()))
+            .count();
+        let armored = (0..trials)
+            .filter(|_| mass_to_chance(100, 0, 100, 200, &mut thread_rng()))
+            .count();
+
+        assert!(armored < unarmored / 2);
+    }
+
+    #[test]
+    fn test_attack_power_raises_eat_chance() {
+        let trials = 10_000;
+        let weak = (0..trials)
+            .filter(|_| mass_to_chance(100, 0, 100, 0, &mut thread_rng()))
+            .count();
+        let strong = (0..trials)
+            .filter(|_| mass_to_chance(100, 255, 100, 0, &mut thread_rng()))
+            .count();
+
+        assert!(strong > weak * 5 / 4);
+    }
+
+    #[test]
+    fn test_huge_masses_do_not_break_eat_chance() {
+        let mut rng = thread_rng();
+        assert!(mass_to_chance(usize::MAX, 255, 0, 0, &mut rng));
+        assert!(!mass_to_chance(0, 0, usize::MAX, 0, &mut rng));
+        mass_to_chance(usize::MAX, 255, usize::MAX, 255, &mut rng);
+    }
+
+    #[test]
+    fn test_linked_child_receives_parent_energy() {
+        let config = WorldConfig {
+            allow_multicell: true,
+            link_share_rate: 50,
+            ..stable_config()
+        };
+        let mut world = World::empty::<5, 5>(config);
+        let light = world.get_light(2);
+
+        world.field[(2, 2)] = WorldCell::Organism(Box::new(Organism::green(150)));
+        let mut child = Organism::green(40);
+        child.set_parent_link(Some(Direction::Left));
+        world.field[(2, 3)] = WorldCell::Organism(Box::new(child));
+
+        world.tick();
+
+        assert!(get_organism(&world, (2, 3)).get_energy() > 40 + light);
+        assert!(get_organism(&world, (2, 2)).get_energy() < 150);
+    }
+
+    #[test]
+    fn test_same_seed_same_checksum() {
+        let run = || {
+            let mut world = World::seeded::<30, 20>(Default::default(), 42);
+            world.populate_random(100);
+            world.populate_green(50);
+            for _ in 0..1000 {
+                world.tick();
+            }
+            world.checksum()
+        };
+
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn test_blocked_move_feedback() {
+        for feedback in [false, true] {
+            let mut world = World::empty::<5, 5>(WorldConfig {
+                blocked_action_feedback: feedback,
+                ..stable_config()
+            });
+            // default direction register points up
+            world.field[(0, 2)] =
+                WorldCell::Organism(single_op_organism(100, OpCode::MoveRelative));
+
+            world.tick();
+
+            let expected = if feedback { 255 } else { 0 };
+            assert_eq!(get_organism(&world, (0, 2)).get_registers()[9], expected);
+
+            // feedback does not outlive the tick after blocked action
+            let bot = mem::replace(&mut world.field[(0, 2)], WorldCell::Empty);
+            world.field[(3, 2)] = bot;
+            world.tick();
+            assert_eq!(get_organism(&world, (2, 2)).get_registers()[9], 0);
+        }
+    }
+
+    #[test]
+    fn test_step_green_cell() {
+        let mut world = World::empty::<5, 5>(stable_config());
+        world.field[(0, 2)] = WorldCell::Organism(Box::new(Organism::green(100)));
+        world.field[(1, 2)] = WorldCell::Organism(Box::new(Organism::green(100)));
+
+        assert!(world.step_cell((2, 2)).is_err());
+        assert!(world.step_cell((0, 5)).is_err());
+        world.step_cell((0, 2)).unwrap();
+
+        // one unit of energy is lost to upkeep
+        let light = world.get_light(0);
+        assert_eq!(get_organism(&world, (0, 2)).get_energy(), 100 + light - 1);
+        assert_eq!(get_organism(&world, (1, 2)).get_energy(), 100);
+    }
+
+    #[test]
+    fn test_senescence_penalizes_old_organisms() {
+        let config = WorldConfig {
+            senescence_rate: 10,
+            ..stable_config()
+        };
+        let mut world = World::empty::<5, 5>(config.clone());
+
+        let mut old = Organism::green(100);
+        for _ in 0..1000 {
+            old.age(&config.aging_mutation_freq, &mut thread_rng());
+        }
+        world.field[(0, 1)] = WorldCell::Organism(Box::new(old));
+        world.field[(0, 3)] = WorldCell::Organism(Box::new(Organism::green(100)));
+
+        world.tick();
+
+        let old_energy = get_organism(&world, (0, 1)).get_energy();
+        let young_energy = get_organism(&world, (0, 3)).get_energy();
+        assert_eq!(young_energy - old_energy, 10);
+    }
+
+    #[test]
+    fn test_energy_speed_scaling() {
+        let mut world = World::empty::<5, 10>(WorldConfig {
+            energy_speed_scaling: 100,
+            max_speed: 3,
+            ..stable_config()
+        });
+        // default direction register points up
+        world.field[(9, 1)] = WorldCell::Organism(single_op_organism(200, OpCode::MoveRelative));
+        world.field[(9, 3)] = WorldCell::Organism(single_op_organism(50, OpCode::MoveRelative));
+
+        world.tick();
+
+        assert!(matches!(world.field[(6, 1)], WorldCell::Organism(..)));
+        assert!(matches!(world.field[(8, 3)], WorldCell::Organism(..)));
+    }
+
+    #[test]
+    fn test_deterministic_combat() {
+        let config = WorldConfig {
+            deterministic_combat: true,
+            ..stable_config()
+        };
+
+        for _ in 0..20 {
+            for (attacker_energy, should_eat) in [(150, true), (60, false)] {
+                let mut world = World::empty::<5, 5>(config.clone());
+                // default direction register points up
+                world.field[(2, 2)] =
+                    WorldCell::Organism(single_op_organism(attacker_energy, OpCode::Eat));
+                world.field[(1, 2)] = WorldCell::Organism(Box::new(Organism::green(100)));
+
+                world.tick();
+
+                assert_eq!(matches!(world.field[(1, 2)], WorldCell::Empty), should_eat);
+            }
+        }
+    }
+
+    #[test]
+    fn test_night_reduces_light() {
+        let mut world = World::empty::<5, 5>(WorldConfig {
+            day_length: 10,
+            night_light_percent: 0,
+            ..stable_config()
+        });
+        let full_light = (world.config.light_behaviour)(0);
+
+        for _ in 0..10 {
+            let expected = if world.get_iteration() % 10 >= 5 {
+                0
+            } else {
+                full_light
+            };
+            assert_eq!(world.get_light(0), expected);
+            world.tick();
+        }
+    }
+
+    #[test]
+    fn test_partial_populate() {
+        let mut world = World::empty::<5, 5>(stable_config());
+        assert_eq!(world.populate_green(23).placed, 23);
+
+        let result = world.populate_random(5);
+        assert_eq!(result.placed, 2);
+        assert_eq!(result.requested, 5);
+
+        assert_eq!(world.populate_green(1).placed, 0);
+    }
+
+    #[test]
+    fn test_clear_dead() {
+        let mut world = World::empty::<5, 5>(stable_config());
+        world.field[(0, 0)] = WorldCell::DeadBody(10, 0, 0);
+        world.field[(4, 4)] = WorldCell::DeadBody(10, 5, 0);
+        for j in 0..5 {
+            world.field[(2, j)] = WorldCell::Organism(Box::new(Organism::green(100)));
+        }
+
+        assert_eq!(world.clear_dead(), 2);
+        assert!(!world
+            .field
+            .inner
+            .iter()
+            .any(|cell| matches!(cell, WorldCell::DeadBody(..))));
+        assert_eq!(world.count_alive(), 5);
+    }
+
+    #[test]
+    fn test_mineral_capacity_independent_of_cell_size() {
+        let mut config = WorldConfig {
+            max_minerals: 100,
+            ..stable_config()
+        };
+        config.max_cell_size = 10;
+        assert_eq!(config.mineral_capacity(500), 100);
+        config.max_cell_size = 10_000;
+        assert_eq!(config.mineral_capacity(500), 100);
+
+        config.mineral_capacity_fn = Some(|energy| energy / 2);
+        assert_eq!(config.mineral_capacity(500), 250);
+
+        let mut bot = Organism::green(100);
+        bot.add_minerals(1000, config.mineral_capacity(bot.get_energy()));
+        assert_eq!(bot.get_minerals(), 50);
+    }
+
+    #[test]
+    fn test_death_inheritance_goes_to_kin() {
+        let config = WorldConfig {
+            death_inheritance: true,
+            dead_energy: 20,
+            ..stable_config()
+        };
+        let mut world = World::empty::<5, 5>(config);
+
+        let place = |world: &mut World, pos, energy, lineage| {
+            let mut bot = single_op_organism(energy, OpCode::Sythesize);
+            bot.set_lineage(lineage);
+            world.field[pos] = WorldCell::Organism(bot);
+        };
+        place(&mut world, (2, 2), 0, 1);
+        place(&mut world, (1, 2), 100, 1);
+        place(&mut world, (2, 3), 100, 1);
+        place(&mut world, (3, 2), 100, 2);
+        place(&mut world, (4, 4), 0, 1);
+
+        // body energy is split between two kin neighbours, other lineage gets nothing
+        world.step_cell((2, 2)).unwrap();
+        assert!(matches!(world.field[(2, 2)], WorldCell::DeadBody(0, ..)));
+        assert_eq!(get_organism(&world, (1, 2)).get_energy(), 110);
+        assert_eq!(get_organism(&world, (2, 3)).get_energy(), 110);
+        assert_eq!(get_organism(&world, (3, 2)).get_energy(), 100);
+
+        // without heirs whole body stays with the corpse
+        world.step_cell((4, 4)).unwrap();
+        assert!(matches!(world.field[(4, 4)], WorldCell::DeadBody(20, ..)));
+    }
+
+    #[test]
+    fn test_depth_mismatch_penalty() {
+        let config = WorldConfig {
+            depth_mismatch_penalty: 100,
+            ..stable_config()
+        };
+        let mut world = World::empty::<5, 128>(config);
+
+        // preferred depths of 4 and 80 are rows 2 and 40 of 128 rows high world
+        let mut adapted = single_op_organism(100, OpCode::Sythesize);
+        adapted.set_preferred_depth(4);
+        let mut misplaced = single_op_organism(100, OpCode::Sythesize);
+        misplaced.set_preferred_depth(80);
+        world.field[(2, 0)] = WorldCell::Organism(adapted);
+        world.field[(2, 3)] = WorldCell::Organism(misplaced);
+
+        world.step_cell((2, 0)).unwrap();
+        world.step_cell((2, 3)).unwrap();
+
+        let adapted = get_organism(&world, (2, 0)).get_energy();
+        let misplaced = get_organism(&world, (2, 3)).get_energy();
+        assert_eq!(adapted - misplaced, 38);
+    }
+
+    #[test]
+    fn test_metabolism_scales_income_and_upkeep() {
+        let config = WorldConfig {
+            max_cell_size: 100,
+            ..stable_config()
+        };
+        let mut world = World::empty::<5, 5>(config);
+
+        let mut changes = vec![];
+        for metabolism in [0, 255] {
+            let mut bot = single_op_organism(1000, OpCode::Sythesize);
+            bot.set_metabolism(metabolism);
+
+            bot.tick(&world, (0, 0));
+            let gained = bot.get_energy() - 1000;
+            world.run_bot_postlude((0, 0), &mut bot);
+            let lost = 1000 + gained - bot.get_energy();
+            changes.push((gained, lost));
+        }
+
+        let (slow, fast) = (changes[0], changes[1]);
+        assert!(fast.0 > slow.0);
+        assert!(fast.1 > slow.1);
+    }
+
+    #[test]
+    fn test_after_action_hook_fires_per_organism() {
+        let mut world = World::empty::<10, 10>(stable_config());
+        world.populate_green(7);
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        world.hooks.after_action = Some(Box::new({
+            let calls = calls.clone();
+            move |_, _| {
+                calls.fetch_add(1, Ordering::Relaxed);
+            }
+        }));
+
+        let alive = world.count_alive();
+        world.tick();
+        assert_eq!(calls.load(Ordering::Relaxed), alive);
+    }
+
+    #[test]
+    fn test_min_clone_parent_energy() {
+        let config = WorldConfig {
+            min_clone_parent_energy: 500,
+            ..stable_config()
+        };
+
+        for (energy, expected_alive) in [(500, 2), (499, 1)] {
+            let mut world = World::empty::<5, 5>(config.clone());
+            world.field[(2, 2)] = WorldCell::Organism(single_op_organism(energy, OpCode::Clone));
+            world.step_cell((2, 2)).unwrap();
+            assert_eq!(world.count_alive(), expected_alive);
+        }
+    }
+
+    #[test]
+    fn test_bottom_edge_mineral_flux() {
+        let config = WorldConfig {
+            minerals_behaviour: |_| 0,
+            edge_flux: EdgeFlux {
+                bottom_minerals: 7,
+                ..Default::default()
+            },
+            ..stable_config()
+        };
+        let mut world = World::empty::<5, 5>(config);
+        world.field[(4, 2)] = WorldCell::Organism(single_op_organism(100, OpCode::Sythesize));
+        world.field[(2, 2)] = WorldCell::Organism(single_op_organism(100, OpCode::Sythesize));
+
+        world.step_cell((4, 2)).unwrap();
+        world.step_cell((2, 2)).unwrap();
+
+        assert_eq!(get_organism(&world, (4, 2)).get_minerals(), 7);
+        assert_eq!(get_organism(&world, (2, 2)).get_minerals(), 0);
+    }
+
+    #[test]
+    fn test_spore_survives_darkness_and_germinates() {
+        let config = WorldConfig {
+            light_behaviour: |_| 0,
+            germination_light: 2,
+            ..stable_config()
+        };
+        let mut world = World::empty::<5, 5>(config);
+        world.field[(0, 2)] = WorldCell::Organism(single_op_organism(100, OpCode::Sporulate));
+
+        world.tick();
+        let energy = get_organism(&world, (0, 2)).get_energy();
+        assert!(get_organism(&world, (0, 2)).is_dormant());
+
+        for _ in 0..50 {
+            world.tick();
+        }
+        assert!(get_organism(&world, (0, 2)).is_dormant());
+        assert_eq!(get_organism(&world, (0, 2)).get_energy(), energy);
+        assert_eq!(get_organism(&world, (0, 2)).get_age(), 1);
+
+        world.config.light_behaviour = |_| 2;
+        world.tick();
+        assert!(!get_organism(&world, (0, 2)).is_dormant());
+    }
+
+    #[test]
+    fn test_reported_seed_replays_population() {
+        let mut original = World::empty::<10, 10>(stable_config());
+        original.populate_random(20);
+
+        let mut replay = World::seeded::<10, 10>(stable_config(), original.get_seed());
+        replay.populate_random(20);
+        assert_eq!(replay.get_seed(), original.get_seed());
+        assert_eq!(replay.checksum(), original.checksum());
+
+        replay.reseed(5);
+        assert_eq!(replay.get_seed(), 5);
+    }
+
+    #[test]
+    fn test_self_destruct_feeds_neighbours() {
+        let config = WorldConfig {
+            minerals_behaviour: |_| 0,
+            ..stable_config()
+        };
+        let mut world = World::empty::<5, 5>(config);
+        let mut bomb = single_op_organism(200, OpCode::SelfDestruct);
+        bomb.add_minerals(20, 100);
+        world.field[(2, 2)] = WorldCell::Organism(bomb);
+        world.field[(1, 2)] = WorldCell::Organism(single_op_organism(50, OpCode::Sythesize));
+        world.field[(2, 3)] = WorldCell::Organism(single_op_organism(50, OpCode::Sythesize));
+
+        world.step_cell((2, 2)).unwrap();
+
+        assert!(matches!(world.field[(2, 2)], WorldCell::Empty));
+        for pos in [(1, 2), (2, 3)] {
+            let neighbour = get_organism(&world, pos);
+            assert_eq!(neighbour.get_energy(), 150);
+            assert_eq!(neighbour.get_minerals(), 10);
+        }
+    }
+
+    #[test]
+    fn test_self_destruct_scatters_corpses() {
+        let config = WorldConfig {
+            self_destruct_mode: SelfDestructMode::Scatter,
+            minerals_behaviour: |_| 0,
+            ..stable_config()
+        };
+        let mut world = World::empty::<5, 5>(config);
+        world.field[(2, 2)] = WorldCell::Organism(single_op_organism(500, OpCode::SelfDestruct));
+        world.field[(1, 2)] = WorldCell::Organism(single_op_organism(50, OpCode::Sythesize));
+
+        world.step_cell((2, 2)).unwrap();
+
+        for pos in [(2, 2), (2, 3), (3, 2), (2, 1)] {
+            assert!(matches!(world.field[pos], WorldCell::DeadBody(125, 0, _)));
+        }
+        assert_eq!(get_organism(&world, (1, 2)).get_energy(), 50);
+    }
+
+    #[test]
+    fn test_two_actions_per_tick() {
+        let config = WorldConfig {
+            actions_per_tick: 2,
+            ..stable_config()
+        };
+        let mut world = World::empty::<5, 5>(config);
+        // move right twice
+        let mut code = [OpCode::MoveRelative; CODE_SIZE];
+        code[0] = OpCode::LoadInt(1);
+        code[1] = OpCode::CopyRegisters(0x02.into());
+        world.field[(2, 0)] =
+            WorldCell::Organism(Box::new(Organism::with_program(100, 0, Program { code })));
+
+        world.step_cell((2, 0)).unwrap();
+        assert!(matches!(world.field[(2, 2)], WorldCell::Organism(..)));
+
+        world.config.actions_per_tick = 1;
+        world.step_cell((2, 2)).unwrap();
+        assert!(matches!(world.field[(2, 3)], WorldCell::Organism(..)));
+    }
+
+    #[test]
+    fn test_complexity_cost() {
+        let config = WorldConfig {
+            complexity_cost: 10,
+            ..stable_config()
+        };
+        let mut world = World::empty::<5, 5>(config);
+
+        let mut bloated = single_op_organism(100, OpCode::Sythesize);
+        let mut code = [OpCode::Sythesize; CODE_SIZE];
+        code[1] = OpCode::JumpUnconditional(255);
+        let mut compact = Box::new(Organism::with_program(100, 0, Program { code }));
+
+        world.run_bot_prelude((2, 2), &mut bloated);
+        world.run_bot_prelude((2, 2), &mut compact);
+        assert_eq!(bloated.get_energy(), 100 - 25);
+        assert_eq!(compact.get_energy(), 100);
+    }
+
+    #[test]
+    fn test_population_floor_refill() {
+        let config = WorldConfig {
+            population_floor: 10,
+            ..stable_config()
+        };
+        let mut world = World::empty::<10, 10>(config);
+        world.populate_green(12);
+        world.apply_disaster(Disaster::Plague { fraction: 1.0 });
+        assert_eq!(world.count_alive(), 0);
+
+        world.tick();
+        assert_eq!(world.count_alive(), 10);
+    }
+
+    #[test]
+    fn test_birth_heatmap_decays() {
+        let config = WorldConfig {
+            heatmap_retain: 50,
+            ..stable_config()
+        };
+        let mut world = World::empty::<5, 5>(config);
+        world.field[(2, 2)] = WorldCell::Organism(single_op_organism(1000, OpCode::Clone));
+
+        world.step_cell((2, 2)).unwrap();
+        // child is placed above the parent
+        let birth_idx = 5 + 2;
+        assert_eq!(world.births.get(birth_idx), 1.0);
+        assert_eq!(world.births.normalized(5, 5)[1][2], 255);
+
+        world.tick();
+        world.tick();
+        assert_eq!(world.births.get(birth_idx), 0.25);
+    }
+
+    #[test]
+    fn test_offspring_count() {
+        let config = WorldConfig {
+            clutch_size: 3,
+            ..stable_config()
+        };
+        let mut world = World::empty::<5, 5>(config);
+        world.field[(2, 2)] = WorldCell::Organism(single_op_organism(1000, OpCode::Clone));
+
+        world.step_cell((2, 2)).unwrap();
+        assert_eq!(get_organism(&world, (2, 2)).get_offspring_count(), 3);
+
+        let stats = world.reproduction_stats();
+        assert_eq!(stats.living.get(&3), Some(&1));
+        assert_eq!(stats.living.get(&0), Some(&3));
+        assert!(stats.completed.is_empty());
+    }
+
+    #[test]
+    fn test_top_down_placement() {
+        let config = WorldConfig {
+            placement_order: PlacementOrder::TopDown,
+            ..stable_config()
+        };
+        let mut world = World::empty::<4, 5>(config);
+        world.populate_green(6);
+
+        for j in 0..4 {
+            assert!(matches!(world.field[(0, j)], WorldCell::Organism(..)));
+        }
+        assert!(matches!(world.field[(1, 0)], WorldCell::Organism(..)));
+        assert!(matches!(world.field[(1, 1)], WorldCell::Organism(..)));
+        assert_eq!(world.count_alive(), 6);
+
+        world.config.placement_order = PlacementOrder::CenterOut;
+        world.populate_green(1);
+        assert!(matches!(world.field[(2, 2)], WorldCell::Organism(..)));
+    }
+
+    #[test]
+    fn test_energy_flow_of_synthesis() {
+        let config = WorldConfig {
+            track_energy_flow: true,
+            light_behaviour: |i| 10 - i,
+            ..stable_config()
+        };
+        let mut world = World::empty::<5, 5>(config);
+        // one organism per column so that none of them is shaded
+        let mut expected = 0;
+        for j in 0..5 {
+            world.field[(j, j)] = WorldCell::Organism(Box::new(Organism::green(100)));
+            expected += world.get_light(j);
+        }
+
+        world.tick();
+        let flow = world.get_energy_flow();
+        assert_eq!(flow.synthesized, expected);
+        assert_eq!(flow.eaten + flow.minerals + flow.shared, 0);
+    }
+
+    #[test]
+    fn test_predator_remembers_prey() {
+        let config = WorldConfig {
+            deterministic_combat: true,
+            ..stable_config()
+        };
+        let mut world = World::empty::<5, 5>(config);
+        let mut code = [OpCode::Sythesize; CODE_SIZE];
+        code[0] = OpCode::Eat;
+        code[1] = OpCode::SenseLastPrey;
+        code[3] = OpCode::SenseLastPrey;
+        world.field[(2, 2)] =
+            WorldCell::Organism(Box::new(Organism::with_program(150, 0, Program { code })));
+        world.field[(1, 2)] = WorldCell::Organism(Box::new(Organism::green(10)));
+
+        world.step_cell((2, 2)).unwrap();
+        assert_eq!(world.count_alive(), 1);
+
+        world.field[(1, 2)] = WorldCell::Organism(Box::new(Organism::green(10)));
+        world.step_cell((2, 2)).unwrap();
+        assert_eq!(get_organism(&world, (2, 2)).get_registers()[0], 1);
+
+        let unfamiliar = single_op_organism(10, OpCode::Eat);
+        world.field[(1, 2)] = WorldCell::Organism(unfamiliar);
+        world.step_cell((2, 2)).unwrap();
+        assert_eq!(get_organism(&world, (2, 2)).get_registers()[0], 0);
+    }
+
+    #[test]
+    fn test_digestion_blocks_eating() {
+        let config = WorldConfig {
+            deterministic_combat: true,
+            digestion_ticks: 3,
+            ..stable_config()
+        };
+        let mut world = World::empty::<5, 5>(config);
+        world.field[(2, 2)] = WorldCell::Organism(single_op_organism(150, OpCode::Eat));
+
+        let mut eaten_at = vec![];
+        for tick in 0..7 {
+            world.field[(1, 2)] = WorldCell::Organism(Box::new(Organism::green(10)));
+            world.step_cell((2, 2)).unwrap();
+            if matches!(world.field[(1, 2)], WorldCell::Empty) {
+                eaten_at.push(tick);
+            }
+        }
+        assert_eq!(eaten_at, vec![0, 3, 6]);
+    }
+
+    #[test]
+    fn test_graze_keeps_target_alive() {
+        let config = WorldConfig {
+            graze_amount: 15,
+            graze_cost: 3,
+            ..stable_config()
+        };
+        let mut world = World::empty::<5, 5>(config);
+        world.field[(2, 2)] = WorldCell::Organism(single_op_organism(100, OpCode::Graze));
+        world.field[(1, 2)] = WorldCell::Organism(Box::new(Organism::green(100)));
+
+        world.step_cell((2, 2)).unwrap();
+        assert_eq!(get_organism(&world, (1, 2)).get_energy(), 85);
+
+        world.field[(1, 2)] = WorldCell::Organism(Box::new(Organism::green(5)));
+        world.step_cell((2, 2)).unwrap();
+        assert_eq!(get_organism(&world, (1, 2)).get_energy(), 0);
+    }
+
+    #[test]
+    fn test_fusion_with_kin() {
+        let config = WorldConfig {
+            allow_fusion: true,
+            minerals_behaviour: |_| 0,
+            ..stable_config()
+        };
+        let max_cell_size = config.max_cell_size;
+        let mut world = World::empty::<5, 5>(config);
+        let mut fuser = single_op_organism(100, OpCode::Fuse);
+        fuser.add_minerals(10, 100);
+        fuser.set_lineage(1);
+        let mut kin = Organism::green(50);
+        kin.add_minerals(5, 100);
+        kin.set_lineage(1);
+        world.field[(2, 2)] = WorldCell::Organism(fuser);
+        world.field[(1, 2)] = WorldCell::Organism(Box::new(kin));
+
+        world.step_cell((2, 2)).unwrap();
+        assert_eq!(world.count_alive(), 1);
+        assert!(matches!(world.field[(1, 2)], WorldCell::Empty));
+        let fused = get_organism(&world, (2, 2));
+        assert_eq!(
+            fused.get_energy(),
+            150 - energy_soft_cap(150, max_cell_size)
+        );
+        assert_eq!(fused.get_minerals(), 15);
+        assert_eq!(fused.get_program().code, [OpCode::Fuse; CODE_SIZE]);
+    }
+
+    #[test]
+    fn test_fusion_skips_other_lineage() {
+        let config = WorldConfig {
+            allow_fusion: true,
+            minerals_behaviour: |_| 0,
+            ..stable_config()
+        };
+        let mut world = World::empty::<5, 5>(config);
+        let mut fuser = single_op_organism(100, OpCode::Fuse);
+        fuser.set_lineage(1);
+        let mut stranger = Organism::green(50);
+        stranger.set_lineage(2);
+        world.field[(2, 2)] = WorldCell::Organism(fuser);
+        world.field[(1, 2)] = WorldCell::Organism(Box::new(stranger));
+
+        world.step_cell((2, 2)).unwrap();
+        assert_eq!(world.count_alive(), 2);
+        assert_eq!(get_organism(&world, (1, 2)).get_energy(), 50);
+    }
+
+    #[test]
+    fn test_fusion_is_capped_by_max_cell_size() {
+        let config = WorldConfig {
+            allow_fusion: true,
+            minerals_behaviour: |_| 0,
+            max_cell_size: 120,
+            max_minerals: 20,
+            mineral_capacity_fn: None,
+            ..stable_config()
+        };
+        let mut world = World::empty::<5, 5>(config);
+        let mut fuser = single_op_organism(100, OpCode::Fuse);
+        fuser.add_minerals(15, 20);
+        fuser.set_lineage(1);
+        let mut kin = Organism::green(50);
+        kin.add_minerals(15, 20);
+        kin.set_lineage(1);
+        world.field[(2, 2)] = WorldCell::Organism(fuser);
+        world.field[(1, 2)] = WorldCell::Organism(Box::new(kin));
+
+        world.step_cell((2, 2)).unwrap();
+        assert_eq!(world.count_alive(), 1);
+        // excess of both resources is discarded together with the freed cell
+        let fused = get_organism(&world, (2, 2));
+        assert!(fused.get_energy() <= 120);
+        assert_eq!(fused.get_minerals(), 20);
+        assert!(matches!(world.field[(1, 2)], WorldCell::Empty));
+    }
+
+    #[test]
+    fn test_free_cells_are_row_major() {
+        let mut world = World::empty::<3, 2>(stable_config());
+        assert_eq!(
+            world.get_free_cells(),
+            vec![(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2)]
+        );
+
+        world.populate_green(6);
+        world.field[(1, 0)] = WorldCell::Empty;
+        assert_eq!(world.get_free_cells(), vec![(1, 0)]);
+    }
+
+    #[test]
+    fn test_glow_lights_neighbours() {
+        for parallel_tick in [false, true] {
+            let config = WorldConfig {
+                allow_bioluminescence: true,
+                glow_light: 4,
+                light_behaviour: |_| 0,
+                parallel_tick,
+                ..stable_config()
+            };
+            let mut world = World::empty::<5, 5>(config);
+            world.field[(2, 2)] = WorldCell::Organism(single_op_organism(100, OpCode::Glow));
+            world.field[(1, 2)] = WorldCell::Organism(Box::new(Organism::green(100)));
+
+            // upper neighbour is scanned before the glowing cell, so it only sees glow next tick
+            world.tick();
+            let energy = get_organism(&world, (1, 2)).get_energy();
+            assert_eq!(
+                energy,
+                100 - energy_soft_cap(100, world.config.max_cell_size)
+            );
+            assert_eq!(world.effective_light((1, 2)), 4);
+            assert_eq!(world.effective_light((2, 2)), 4);
+            assert_eq!(world.effective_light((0, 2)), 0);
+
+            world.tick();
+            assert_eq!(
+                get_organism(&world, (1, 2)).get_energy(),
+                energy + 4 - energy_soft_cap(energy + 4, world.config.max_cell_size)
+            );
+
+            world.field[(2, 2)] = WorldCell::Empty;
+            world.tick();
+            assert_eq!(world.effective_light((1, 2)), 0);
+        }
+    }
+
+    fn directed_organism(energy: usize, direction: u8, op: OpCode) -> Box<Organism> {
+        let mut code = [op; CODE_SIZE];
+        code[0] = OpCode::LoadInt(direction);
+        code[1] = OpCode::CopyRegisters(0x02.into());
+        Box::new(Organism::with_program(energy, 0, Program { code }))
+    }
+
+    #[test]
+    fn test_parallel_tick_lowest_index_wins() {
+        let mut world = World::empty::<5, 5>(WorldConfig {
+            parallel_tick: true,
+            ..stable_config()
+        });
+        // both organisms try to move into (2, 2)
+        world.field[(2, 1)] = WorldCell::Organism(directed_organism(100, 1, OpCode::MoveRelative));
+        world.field[(2, 3)] = WorldCell::Organism(directed_organism(100, 3, OpCode::MoveRelative));
+
+        world.tick();
+
+        assert!(matches!(world.field[(2, 1)], WorldCell::Empty));
+        assert!(matches!(world.field[(2, 2)], WorldCell::Organism(..)));
+        assert!(matches!(world.field[(2, 3)], WorldCell::Organism(..)));
+    }
+
+    #[test]
+    fn test_parallel_tick_skips_eaten_organism() {
+        let mut world = World::empty::<5, 5>(WorldConfig {
+            parallel_tick: true,
+            deterministic_combat: true,
+            ..stable_config()
+        });
+        // predator eats down before prey gets to move right
+        world.field[(1, 2)] = WorldCell::Organism(directed_organism(150, 2, OpCode::Eat));
+        world.field[(2, 2)] = WorldCell::Organism(directed_organism(50, 1, OpCode::MoveRelative));
+
+        world.tick();
+
+        assert!(matches!(world.field[(2, 2)], WorldCell::Empty));
+        assert!(matches!(world.field[(2, 3)], WorldCell::Empty));
+        assert_eq!(world.count_alive(), 1);
+    }
+
+    #[test]
+    fn test_parallel_tick_skips_newborn_in_planned_cell() {
+        let mut world = World::empty::<5, 5>(WorldConfig {
+            parallel_tick: true,
+            deterministic_combat: true,
+            mutation_chance: 0,
+            ..stable_config()
+        });
+        // predator eats prey and splits into the only free cell, which is where prey was
+        world.field[(1, 2)] = WorldCell::Organism(directed_organism(400, 2, OpCode::Eat));
+        world.field[(2, 2)] = WorldCell::Organism(directed_organism(50, 1, OpCode::MoveRelative));
+        for pos in [(0, 2), (1, 1), (1, 3)] {
+            world.field[pos] = WorldCell::DeadBody(10, 0, 0);
+        }
+
+        world.tick();
+
+        let child = get_organism(&world, (2, 2));
+        assert_eq!(
+            child.get_program(),
+            get_organism(&world, (1, 2)).get_program()
+        );
+        assert_eq!(child.get_age(), 0);
+        assert!(matches!(world.field[(2, 3)], WorldCell::Empty));
+    }
+
+    #[test]
+    fn test_parallel_tick_is_deterministic() {
+        let run = || {
+            let mut world = World::benchmark_scenario("predator_prey", 42).unwrap();
+            world.config.parallel_tick = true;
+            for _ in 0..20 {
+                world.tick();
+            }
+            world.checksum()
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn test_failed_split_returns_resources() {
+        let surrounded = |config: WorldConfig| {
+            let mut world = World::empty::<5, 5>(config);
+            world.field[(2, 2)] = WorldCell::Organism(Box::new(Organism::green(300)));
+            for pos in [(1, 2), (3, 2), (2, 1), (2, 3)] {
+                world.field[pos] = WorldCell::DeadBody(10, 0, 0);
+            }
+            world.step_cell((2, 2)).unwrap();
+            *get_organism(&world, (2, 2))
+        };
+
+        let blocked = surrounded(stable_config());
+        let unsplit = surrounded(WorldConfig {
+            split_behaviour: |_, _| Err(()),
+            ..stable_config()
+        });
+
+        assert_eq!(blocked.get_energy(), unsplit.get_energy());
+        assert_eq!(blocked.get_minerals(), unsplit.get_minerals());
+        assert_eq!(blocked.get_offspring_count(), 0);
+    }
+
+    #[test]
+    fn test_runtime_dimensions() {
+        let mut world = World::with_dimensions(7, 3, stable_config());
+        assert_eq!((world.get_width(), world.get_height()), (7, 3));
+        assert_eq!(world.populate_green(100).placed, 21);
+
+        world.tick();
+        assert!(matches!(world.field[(2, 6)], WorldCell::Organism(..)));
+        assert!(world.field.get((3, 0)).is_none());
+        // column past the width does not wrap into the next row
+        assert!(world.field.get((0, 9)).is_none());
+    }
+
+    #[test]
+    fn test_sight_does_not_wrap_onto_itself() {
+        let world = World::empty::<5, 5>(WorldConfig {
+            wrap_columns: true,
+            ..stable_config()
+        });
+
+        let (cell, distance) = world.look_far((2, 2), Direction::Right, 10);
+        assert!(matches!(cell, Some(WorldCell::Empty)));
+        assert_eq!(distance, 4);
+        assert_eq!(world.openness((2, 2), Direction::Left, 10), 4);
+    }
+
+    #[test]
+    fn test_clutch_fills_free_neighbours() {
+        let config = WorldConfig {
+            clutch_size: 3,
+            ..stable_config()
+        };
+        let mut world = World::empty::<5, 5>(config);
+        world.field[(2, 2)] = WorldCell::Organism(single_op_organism(1000, OpCode::Clone));
+        world.field[(2, 1)] = WorldCell::DeadBody(10, 0, 0);
+
+        world.step_cell((2, 2)).unwrap();
+
+        for pos in [(1, 2), (2, 3), (3, 2)] {
+            assert!(get_organism(&world, pos).get_energy() > 0);
+        }
+        assert_eq!(world.count_alive(), 4);
+    }
+
+    #[test]
+    fn test_kin_safe_eating() {
+        let config = WorldConfig {
+            kin_safe_eating: true,
+            deterministic_combat: true,
+            ..stable_config()
+        };
+        let mut world = World::empty::<5, 5>(config);
+
+        let place = |world: &mut World, pos, energy, op, lineage| {
+            let mut bot = single_op_organism(energy, op);
+            bot.set_lineage(lineage);
+            world.field[pos] = WorldCell::Organism(bot);
+        };
+
+        // eat instruction targets cell above
+        place(&mut world, (2, 2), 150, OpCode::Eat, 1);
+        place(&mut world, (1, 2), 50, OpCode::Sythesize, 1);
+        world.step_cell((2, 2)).unwrap();
+        assert_eq!(get_organism(&world, (1, 2)).get_energy(), 50);
+        assert_eq!(get_organism(&world, (2, 2)).get_energy(), 149);
+
+        place(&mut world, (1, 2), 50, OpCode::Sythesize, 2);
+        world.step_cell((2, 2)).unwrap();
+        assert!(matches!(world.field[(1, 2)], WorldCell::Empty));
+    }
+
+    #[test]
+    fn test_distance_across_seam() {
+        let mut world = World::empty::<10, 8>(stable_config());
+
+        // default topology: columns wrap, rows are bounded
+        assert_eq!(world.toroidal_distance((0, 1), (0, 8)), (0, 3));
+        assert_eq!(
+            world.direction_toward((0, 1), (0, 8)),
+            Some(Direction::Left)
+        );
+        assert_eq!(
+            world.direction_toward((0, 8), (0, 1)),
+            Some(Direction::Right)
+        );
+        assert_eq!(world.toroidal_distance((0, 0), (7, 0)), (7, 0));
+        assert_eq!(
+            world.direction_toward((0, 0), (7, 0)),
+            Some(Direction::Down)
+        );
+        assert_eq!(world.toroidal_distance((3, 0), (3, 5)), (0, 5));
+        assert_eq!(world.direction_toward((4, 4), (4, 4)), None);
+        assert_eq!(world.relative_shift((0, 9), Direction::Right), Some((0, 0)));
+        assert_eq!(world.relative_shift((0, 9), Direction::Up), None);
+
+        world.config.wrap_rows = true;
+        assert_eq!(world.toroidal_distance((0, 0), (7, 0)), (1, 0));
+        assert_eq!(world.direction_toward((0, 0), (7, 0)), Some(Direction::Up));
+        assert_eq!(
+            world.direction_toward((7, 0), (0, 0)),
+            Some(Direction::Down)
+        );
+        assert_eq!(world.relative_shift((0, 9), Direction::Up), Some((7, 9)));
+        assert_eq!(world.relative_shift((7, 9), Direction::Down), Some((0, 9)));
+
+        world.config.wrap_rows = false;
+        world.config.wrap_columns = false;
+        assert_eq!(world.toroidal_distance((0, 1), (0, 8)), (0, 7));
+        assert_eq!(
+            world.direction_toward((0, 1), (0, 8)),
+            Some(Direction::Right)
+        );
+        assert_eq!(world.relative_shift((0, 9), Direction::Right), None);
+        assert_eq!(world.relative_shift((0, 0), Direction::Left), None);
+        assert_eq!(world.relative_shift((3, 4), Direction::Left), Some((3, 3)));
+    }
+
+    #[test]
+    fn test_start_energy_jitter() {
+        let config = WorldConfig {
+            start_energy: 40,
+            start_energy_jitter: 10,
+            ..stable_config()
+        };
+        let mut world = World::empty::<10, 10>(config);
+        world.populate_green(100);
+
+        let energies: Vec<usize> = world
+            .field
+            .inner
+            .iter()
+            .filter_map(|cell| match cell {
+                WorldCell::Organism(o) => Some(o.get_energy()),
+                _ => None,
+            })
+            .collect();
+
+        assert!(energies.iter().all(|e| (30..=50).contains(e)));
+        assert!(energies.iter().any(|&e| e != energies[0]));
+    }
+
+    #[test]
+    fn test_sense_claimed_territory() {
+        let mut world = World::empty::<5, 5>(stable_config());
+
+        let mut owner = single_op_organism(100, OpCode::ClaimTerritory);
+        owner.set_lineage(1);
+        world.field[(1, 2)] = WorldCell::Organism(owner);
+        world.step_cell((1, 2)).unwrap();
+        assert_eq!(world.territory_owner((1, 2)), Some(1));
+
+        // sense instruction looks up
+        let mut relative = single_op_organism(100, OpCode::SenseTerritory);
+        relative.set_lineage(1);
+        relative.tick(&world, (2, 2));
+        assert_eq!(relative.get_registers()[0], 1);
+
+        let mut stranger = single_op_organism(100, OpCode::SenseTerritory);
+        stranger.set_lineage(2);
+        stranger.tick(&world, (2, 2));
+        assert_eq!(stranger.get_registers()[0], 2);
+
+        stranger.tick(&world, (3, 2));
+        assert_eq!(stranger.get_registers()[0], 0);
+
+        world.config.territory_lifetime = 0;
+        assert_eq!(world.territory_owner((1, 2)), None);
+    }
+
+    #[test]
+    fn test_occlusion_scales_with_energy() {
+        let config = WorldConfig {
+            occlusion_per_energy: 1,
+            light_behaviour: |_| 10,
+            ..stable_config()
+        };
+        let mut world = World::empty::<5, 5>(config);
+        world.field[(0, 1)] = WorldCell::Organism(single_op_organism(500, OpCode::Sythesize));
+        world.field[(0, 3)] = WorldCell::Organism(single_op_organism(100, OpCode::Sythesize));
+
+        world.update_shade();
+
+        assert_eq!(world.effective_light((0, 1)), 10);
+        assert_eq!(world.effective_light((2, 1)), 5);
+        assert_eq!(world.effective_light((2, 3)), 9);
+        assert_eq!(world.effective_light((2, 0)), 10);
+    }
+
+    #[test]
+    fn test_reseed_is_reproducible() {
+        let mut world = World::empty::<20, 20>(stable_config());
+        world.populate_random(50);
+        world.tick();
+
+        let run = |world: &mut World| {
+            world.reseed(11);
+            world.populate_green(30);
+            world.populate_random(30);
+            for _ in 0..5 {
+                world.tick();
+            }
+            world.checksum()
+        };
+
+        let first = run(&mut world);
+        let second = run(&mut world);
+        assert_eq!(first, second);
+        assert_eq!(world.get_iteration(), 6);
+    }
+
+    #[test]
+    fn test_memory_footprint() {
+        let mut world = World::empty::<100, 50>(stable_config());
+        world.populate_green(10);
+
+        let footprint = world.memory_footprint();
+        let expected = 100 * 50 * mem::size_of::<WorldCell>();
+        assert!(footprint.field >= expected && footprint.field <= expected * 11 / 10);
+        assert_eq!(footprint.organisms, 10 * mem::size_of::<Organism>());
+        assert!(footprint.total > footprint.field + footprint.updates);
+    }
+
+    #[test]
+    fn test_every_organism_processed_once_per_tick() {
+        let mut world = World::seeded::<20, 20>(stable_config(), 5);
+
+        // organisms walk in fixed direction forever, age counts processed ticks
+        let walker = |direction: u8| {
+            let mut code = [OpCode::Sythesize; CODE_SIZE];
+            code[0] = OpCode::LoadInt(direction);
+            code[1] = OpCode::CopyRegisters(2.into());
+            code[2] = OpCode::MoveRelative;
+            code[3] = OpCode::JumpUnconditional(253);
+            Box::new(Organism::with_program(150, 0, Program { code }))
+        };
+
+        for id in 0..40 {
+            let mut bot = walker((id % 4) as u8);
+            bot.set_lineage(id);
+            world.field[(id / 4 * 2, id % 4 * 5)] = WorldCell::Organism(bot);
+        }
+
+        for ticks in 1..=20 {
+            world.tick();
+            let mut seen = vec![];
+            for cell in world.field.inner.iter() {
+                if let WorldCell::Organism(o) = cell {
+                    assert_eq!(o.get_age(), ticks, "organism {}", o.get_lineage());
+                    seen.push(o.get_lineage());
+                }
+            }
+            assert_eq!(seen.len(), 40);
+        }
+    }
+
+    #[test]
+    fn test_mutagen_hotspot() {
+        let mut world = World::seeded::<10, 10>(stable_config(), 9);
+        world.paint_mutagen((2, 2), 1, 200);
+
+        let mutated_genes = |world: &World| -> usize {
+            world
+                .field
+                .inner
+                .iter()
+                .filter_map(|cell| match cell {
+                    WorldCell::Organism(o) => Some(
+                        o.get_program()
+                            .iter()
+                            .filter(|op| **op != OpCode::Sythesize)
+                            .count(),
+                    ),
+                    _ => None,
+                })
+                .sum()
+        };
+
+        // energy above split threshold makes organism bud off a child
+        world.field[(2, 2)] = WorldCell::Organism(single_op_organism(400, OpCode::Sythesize));
+        world.step_cell((2, 2)).unwrap();
+        let in_hotspot = mutated_genes(&world);
+
+        world.reseed(9);
+        world.field[(7, 7)] = WorldCell::Organism(single_op_organism(400, OpCode::Sythesize));
+        world.step_cell((7, 7)).unwrap();
+        let outside = mutated_genes(&world);
+
+        assert_eq!(world.count_alive(), 2);
+        assert!(
+            in_hotspot > 10 * outside.max(1),
+            "{} vs {}",
+            in_hotspot,
+            outside
+        );
+    }
+
+    #[test]
+    fn test_huge_mutagen_saturates() {
+        let mut world = World::empty::<5, 5>(stable_config());
+        world.paint_mutagen((0, 0), usize::MAX, usize::MAX);
+        assert_eq!(world.mutation_chance_at((4, 4)), 1000);
+    }
+
+    #[test]
+    fn test_shuffle_follows_seed() {
+        let order = |seed| {
+            let mut world = World::seeded::<5, 5>(stable_config(), seed);
+            let mut items: Vec<usize> = (0..50).collect();
+            world.shuffle(&mut items);
+            items
+        };
+
+        assert_eq!(order(1), order(1));
+        assert_ne!(order(1), order(2));
+    }
+}
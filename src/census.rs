@@ -0,0 +1,318 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::cells::{
+    code::OpCode,
+    organism::Organism,
+    world::{World, WorldCell},
+};
+
+pub const CENSUS_FILE: &str = "census.csv";
+
+pub const CENSUS_HEADER: &str =
+    "iteration,population,dead_bodies,total_energy,total_minerals,mean_age";
+
+/// env variable with destination of JSON Lines census stream, either `stdout`
+/// or path of a file to append to, stream is disabled when unset
+pub const CENSUS_STREAM_VAR: &str = "AQUARIUM_CENSUS_JSONL";
+
+/// number of rows buffered before census file is flushed
+const FLUSH_EVERY: usize = 16;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CensusRow {
+    pub iteration: usize,
+    pub population: usize,
+    pub dead_bodies: usize,
+    pub total_energy: usize,
+    pub total_minerals: usize,
+    pub mean_age: f64,
+}
+
+impl CensusRow {
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{:.2}",
+            self.iteration,
+            self.population,
+            self.dead_bodies,
+            self.total_energy,
+            self.total_minerals,
+            self.mean_age
+        )
+    }
+}
+
+/// organisms grouped by energy sources reachable in their programs
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct RoleCounts {
+    /// synthesis only
+    pub producers: usize,
+    /// eating only
+    pub predators: usize,
+    /// using minerals only
+    pub mineral_users: usize,
+    /// more than one energy source
+    pub mixed: usize,
+    /// no energy source at all
+    pub inert: usize,
+}
+
+impl RoleCounts {
+    fn count(&mut self, organism: &Organism) {
+        let genes = organism.get_program().reachable_genes(organism.get_ip());
+        let has = |op: OpCode| genes.iter().any(|&(_, gene)| gene == op);
+        match (
+            has(OpCode::Sythesize),
+            has(OpCode::Eat),
+            has(OpCode::UseMinerals),
+        ) {
+            (false, false, false) => self.inert += 1,
+            (true, false, false) => self.producers += 1,
+            (false, true, false) => self.predators += 1,
+            (false, false, true) => self.mineral_users += 1,
+            _ => self.mixed += 1,
+        }
+    }
+}
+
+/// census row together with nested breakdowns, streamed as JSON Lines
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Census {
+    #[serde(flatten)]
+    pub totals: CensusRow,
+    pub roles: RoleCounts,
+}
+
+pub fn full_census(world: &World) -> Census {
+    let mut roles = RoleCounts::default();
+    for cell in world.field.inner.iter() {
+        if let WorldCell::Organism(o) = cell {
+            roles.count(o);
+        }
+    }
+    Census {
+        totals: census(world),
+        roles,
+    }
+}
+
+pub fn census(world: &World) -> CensusRow {
+    let mut row = CensusRow {
+        iteration: world.get_iteration(),
+        population: 0,
+        dead_bodies: 0,
+        total_energy: 0,
+        total_minerals: 0,
+        mean_age: 0.0,
+    };
+    let mut total_age = 0;
+
+    for cell in world.field.inner.iter() {
+        match cell {
+            WorldCell::Organism(o) => {
+                row.population += 1;
+                row.total_energy += o.get_energy();
+                row.total_minerals += o.get_minerals();
+                total_age += o.get_age();
+            }
+            WorldCell::DeadBody(..) => row.dead_bodies += 1,
+            WorldCell::Empty => {}
+        }
+    }
+
+    if row.population > 0 {
+        row.mean_age = total_age as f64 / row.population as f64;
+    }
+    row
+}
+
+/// append-only census writer, file is opened lazily on first record.
+/// Write errors are logged and census is disabled instead of stopping the simulation
+#[derive(Debug)]
+pub struct CensusLog {
+    path: PathBuf,
+    writer: Option<BufWriter<File>>,
+    failed: bool,
+    pending_rows: usize,
+}
+
+impl CensusLog {
+    pub fn new(path: &Path) -> Self {
+        CensusLog {
+            path: path.to_path_buf(),
+            writer: None,
+            failed: false,
+            pending_rows: 0,
+        }
+    }
+
+    /// records a census row if current iteration falls on `census_interval`
+    pub fn check(&mut self, world: &World) {
+        let interval = world.config.census_interval;
+        if interval == 0 || self.failed || !world.get_iteration().is_multiple_of(interval) {
+            return;
+        }
+
+        if let Err(e) = self.append(&census(world)) {
+            tracing::error!("failed to write census to {:?}: {}", self.path, e);
+            self.failed = true;
+            self.writer = None;
+        }
+    }
+
+    fn append(&mut self, row: &CensusRow) -> io::Result<()> {
+        if self.writer.is_none() {
+            let is_new = !self.path.exists();
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            let mut writer = BufWriter::new(file);
+            if is_new {
+                writeln!(writer, "{}", CENSUS_HEADER)?;
+            }
+            self.writer = Some(writer);
+        }
+
+        let writer = self.writer.as_mut().unwrap();
+        writeln!(writer, "{}", row.to_csv())?;
+        self.pending_rows += 1;
+        if self.pending_rows >= FLUSH_EVERY {
+            writer.flush()?;
+            self.pending_rows = 0;
+        }
+        Ok(())
+    }
+
+    /// writes out buffered rows, called on shutdown so that they are not lost
+    pub fn flush(&mut self) {
+        if let Some(writer) = self.writer.as_mut() {
+            if let Err(e) = writer.flush() {
+                tracing::error!("failed to flush census to {:?}: {}", self.path, e);
+            }
+            self.pending_rows = 0;
+        }
+    }
+}
+
+/// writes a JSON object per census into a file or stdout, flushing every line.
+/// Like [CensusLog], stream disables itself on write error
+pub struct CensusStream {
+    writer: Box<dyn Write + Send>,
+    failed: bool,
+}
+
+impl CensusStream {
+    pub fn new(writer: Box<dyn Write + Send>) -> Self {
+        CensusStream {
+            writer,
+            failed: false,
+        }
+    }
+
+    /// stream configured by [CENSUS_STREAM_VAR], if any
+    pub fn from_env() -> Option<Self> {
+        let destination = std::env::var(CENSUS_STREAM_VAR).ok()?;
+        if destination == "stdout" {
+            return Some(Self::new(Box::new(io::stdout())));
+        }
+        match OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&destination)
+        {
+            Ok(file) => Some(Self::new(Box::new(file))),
+            Err(e) => {
+                tracing::error!("failed to open census stream {:?}: {}", destination, e);
+                None
+            }
+        }
+    }
+
+    /// streams a census if current iteration falls on `census_interval`
+    pub fn check(&mut self, world: &World) {
+        let interval = world.config.census_interval;
+        if interval == 0 || self.failed || !world.get_iteration().is_multiple_of(interval) {
+            return;
+        }
+
+        if let Err(e) = self.append(&full_census(world)) {
+            tracing::error!("failed to stream census: {}", e);
+            self.failed = true;
+        }
+    }
+
+    fn append(&mut self, census: &Census) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, census)?;
+        writeln!(self.writer)?;
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{census, full_census, CensusLog, CENSUS_HEADER};
+    use crate::cells::{
+        organism::Organism,
+        world::{World, WorldCell},
+    };
+
+    #[test]
+    fn test_census_log_flush_writes_pending_rows() {
+        let path = std::env::temp_dir().join(format!("aquarium-census-{}.csv", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let config = crate::cells::world::WorldConfig {
+            census_interval: 1,
+            ..Default::default()
+        };
+        let world = World::empty::<5, 5>(config);
+        let mut log = CensusLog::new(&path);
+
+        log.check(&world);
+        // single row stays in the buffer until flushed
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+        log.flush();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let lines = written.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], CENSUS_HEADER);
+    }
+
+    #[test]
+    fn test_census_row() {
+        let mut world = World::empty::<5, 5>(Default::default());
+        let mut bot = Organism::green(100);
+        bot.add_minerals(10, 100);
+        world.field[(0, 0)] = WorldCell::Organism(Box::new(bot));
+        world.field[(1, 1)] = WorldCell::Organism(Box::new(Organism::green(50)));
+        world.field[(2, 2)] = WorldCell::DeadBody(20, 5, 0);
+
+        let row = census(&world);
+        assert_eq!(row.to_csv(), "1,2,1,150,10,0.00");
+    }
+
+    #[test]
+    fn test_census_json_line() {
+        let mut world = World::empty::<5, 5>(Default::default());
+        world.field[(0, 0)] = WorldCell::Organism(Box::new(Organism::green(100)));
+        world.field[(2, 2)] = WorldCell::DeadBody(20, 5, 0);
+
+        let line = serde_json::to_string(&full_census(&world)).unwrap();
+        assert!(!line.contains('\n'));
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["iteration"], 1);
+        assert_eq!(value["population"], 1);
+        assert_eq!(value["dead_bodies"], 1);
+        assert_eq!(value["total_energy"], 100);
+        assert_eq!(value["roles"]["producers"], 1);
+        assert_eq!(value["roles"]["predators"], 0);
+    }
+}
@@ -0,0 +1,127 @@
+use std::{
+    future::{ready, Ready},
+    sync::Arc,
+};
+
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    error::ErrorServiceUnavailable,
+    Error,
+};
+use futures::future::LocalBoxFuture;
+use tokio::sync::Semaphore;
+
+/// env variable holding number of api requests allowed to contend for the world at once
+pub const MAX_WORLD_REQUESTS_VAR: &str = "AQUARIUM_MAX_WORLD_REQUESTS";
+
+const DEFAULT_MAX_WORLD_REQUESTS: usize = 32;
+
+/// middleware letting at most given number of requests in at once, excess requests
+/// are rejected with 503 instead of queueing on the world lock and starving the tick thread.
+/// Clones share permits, so a single limit should be created for all server workers
+#[derive(Clone)]
+pub struct ConcurrencyLimit {
+    semaphore: Arc<Semaphore>,
+}
+
+impl ConcurrencyLimit {
+    pub fn new(permits: usize) -> Self {
+        ConcurrencyLimit {
+            semaphore: Arc::new(Semaphore::new(permits.max(1))),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let permits = std::env::var(MAX_WORLD_REQUESTS_VAR)
+            .ok()
+            .and_then(|permits| permits.parse().ok())
+            .unwrap_or(DEFAULT_MAX_WORLD_REQUESTS);
+        Self::new(permits)
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ConcurrencyLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = ConcurrencyLimitMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ConcurrencyLimitMiddleware {
+            service,
+            semaphore: self.semaphore.clone(),
+        }))
+    }
+}
+
+pub struct ConcurrencyLimitMiddleware<S> {
+    service: S,
+    semaphore: Arc<Semaphore>,
+}
+
+impl<S, B> Service<ServiceRequest> for ConcurrencyLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        match self.semaphore.clone().try_acquire_owned() {
+            Ok(permit) => {
+                let fut = self.service.call(req);
+                Box::pin(async move {
+                    let response = fut.await;
+                    drop(permit);
+                    response
+                })
+            }
+            Err(_) => Box::pin(ready(Err(ErrorServiceUnavailable(
+                "too many concurrent requests",
+            )))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use actix_web::{dev::Service, http::StatusCode, test, web, App, HttpResponse};
+
+    use super::ConcurrencyLimit;
+
+    #[actix_web::test]
+    async fn test_excess_request_is_rejected() {
+        let app = test::init_service(
+            App::new()
+                .wrap(ConcurrencyLimit::new(2))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        let request = || test::TestRequest::get().uri("/").to_request();
+
+        // permits are taken on call and held until responses are awaited
+        let first = app.call(request());
+        let second = app.call(request());
+        let rejected = app.call(request()).await.unwrap_err();
+        assert_eq!(
+            rejected.as_response_error().status_code(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+
+        assert_eq!(first.await.unwrap().status(), StatusCode::OK);
+        assert_eq!(second.await.unwrap().status(), StatusCode::OK);
+        let response = app.call(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
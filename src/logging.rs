@@ -0,0 +1,52 @@
+use tracing::Subscriber;
+use tracing_subscriber::{util::SubscriberInitExt, EnvFilter};
+
+/// env variable holding log format, `json` enables structured logs
+pub const LOG_FORMAT_VAR: &str = "AQUARIUM_LOG_FORMAT";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl LogFormat {
+    pub fn from_env() -> Self {
+        match std::env::var(LOG_FORMAT_VAR) {
+            Ok(format) if format.eq_ignore_ascii_case("json") => LogFormat::Json,
+            _ => LogFormat::Text,
+        }
+    }
+}
+
+/// builds subscriber filtered by `RUST_LOG` (defaults to `info`)
+pub fn build_subscriber(format: LogFormat) -> Box<dyn Subscriber + Send + Sync> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+
+    match format {
+        LogFormat::Text => Box::new(builder.finish()),
+        LogFormat::Json => Box::new(builder.json().finish()),
+    }
+}
+
+/// installs global subscriber, records of `log` crate (used by actix) are forwarded to it
+pub fn init(format: LogFormat) {
+    if let Err(e) = build_subscriber(format).try_init() {
+        eprintln!("failed to initialize logging: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{build_subscriber, LogFormat};
+
+    #[test]
+    fn test_subscriber_builds_in_all_formats() {
+        for format in [LogFormat::Text, LogFormat::Json] {
+            tracing::subscriber::with_default(build_subscriber(format), || {
+                tracing::info!(iteration = 1, population = 2, "smoke test");
+            });
+        }
+    }
+}
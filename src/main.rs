@@ -1,67 +1,124 @@
 extern crate rand;
 
-use std::{sync::Arc, time::Instant};
+use std::{path::Path, sync::Arc, time::Instant};
 
-use rand::{distributions::Bernoulli, thread_rng, Rng};
+use rand::{thread_rng, Rng};
 
 use std::thread;
 use std::time::Duration;
 use tokio::task;
 
-use cells::world::{World, WorldField};
+use cells::world::World;
 mod api;
+mod bottleneck;
 mod cells;
+mod census;
 
 mod state;
-use state::ServerState;
+use state::{MState, ServerState};
 
 use crate::cells::world::WorldConfig;
-use crate::state::SpeedMeasure;
+use crate::logging::LogFormat;
 
 mod cachealloc;
+mod limiter;
+mod logging;
+mod rle;
 mod routes;
+mod scheduler;
 mod serialization;
 use actix_web::{App, HttpServer};
 
 const PASSWORD_LETTERS: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
 
+/// env variables holding world size, 100x50 by default
+const WIDTH_VAR: &str = "AQUARIUM_WIDTH";
+const HEIGHT_VAR: &str = "AQUARIUM_HEIGHT";
+const DEFAULT_WIDTH: usize = 100;
+const DEFAULT_HEIGHT: usize = 50;
+
+/// positive dimension read from env variable, `default` if unset or invalid
+fn dimension_from_env(var: &str, default: usize) -> usize {
+    std::env::var(var)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .filter(|&value| value > 0)
+        .unwrap_or(default)
+}
+
+const BENCH_SEED: u64 = 42;
+const BENCH_TICKS: usize = 200;
+
+/// runs every benchmark scenario for a fixed number of ticks with both sequential
+/// and parallel tick and prints timings
+fn run_benchmarks() {
+    for name in cells::scenarios::BENCHMARK_SCENARIOS {
+        for (mode, parallel) in [("sequential", false), ("parallel", true)] {
+            let mut world = World::benchmark_scenario(name, BENCH_SEED).unwrap();
+            world.config.parallel_tick = parallel;
+            let start = Instant::now();
+            for _ in 0..BENCH_TICKS {
+                world.tick();
+            }
+            let elapsed = start.elapsed().as_secs_f64();
+            println!(
+                "{name} ({mode}): {BENCH_TICKS} ticks in {elapsed:.3}s ({:.1} tps), population {}",
+                BENCH_TICKS as f64 / elapsed,
+                world.count_alive()
+            );
+        }
+    }
+}
+
+/// single simulation step of the world together with census and bottleneck bookkeeping
+fn tick_state(state: &MState) {
+    let mut state = state.lock();
+    if state.paused {
+        return;
+    }
+
+    let state = &mut *state;
+    state.world.tick();
+    state.census.check(&state.world);
+    if let Some(stream) = state.census_stream.as_mut() {
+        stream.check(&state.world);
+    }
+    if let Some(snapshot) = state.bottleneck.check(&state.world) {
+        match bottleneck::write_snapshot(Path::new(bottleneck::SNAPSHOT_DIR), &snapshot) {
+            Ok(path) => tracing::info!(
+                iteration = snapshot.iteration,
+                population = snapshot.population,
+                "bottleneck snapshot written to {:?}",
+                path
+            ),
+            Err(e) => tracing::error!("failed to write bottleneck snapshot: {}", e),
+        }
+    }
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    ::std::env::set_var("RUST_LOG", "actix_web=debug");
-    env_logger::init();
-
-    let config = WorldConfig {
-        start_energy: 40,
-        dead_energy: 20,
-        split_behaviour: |energy, minerals| {
-            if energy > 200 {
-                Ok((energy / 2, minerals / 2))
-            } else {
-                Err(())
-            }
-        },
-        light_behaviour: |i| 3usize.saturating_sub(i / 10),
-        mutation_chance: 1,
-        aging_mutation_freq: Bernoulli::from_ratio(1, 1000).unwrap(),
-        max_cell_size: 500,
-        minerals_behaviour: |i| {
-            let distance_from_bottom = 50 - i - 1;
-            3usize.saturating_sub(distance_from_bottom / 10)
-        },
-        max_minerals: 100,
-        attack_cost: 10,
-    };
+    logging::init(LogFormat::from_env());
 
-    let password = std::env::var("WEBUI_PASSWORD")
-        .ok()
-        .map(String::from)
-        .unwrap_or_else(|| {
-            let mut rng = thread_rng();
-            (0..20)
-                .map(|_| rng.gen_range(0..PASSWORD_LETTERS.len()))
-                .map(|idx| PASSWORD_LETTERS.as_bytes()[idx] as char)
-                .collect::<String>()
-        });
+    if std::env::args().any(|arg| arg == "--bench") {
+        run_benchmarks();
+        return Ok(());
+    }
+
+    let (width, height) = (
+        dimension_from_env(WIDTH_VAR, DEFAULT_WIDTH),
+        dimension_from_env(HEIGHT_VAR, DEFAULT_HEIGHT),
+    );
+
+    let config = WorldConfig::default();
+
+    let password = std::env::var("WEBUI_PASSWORD").ok().unwrap_or_else(|| {
+        let mut rng = thread_rng();
+        (0..20)
+            .map(|_| rng.gen_range(0..PASSWORD_LETTERS.len()))
+            .map(|idx| PASSWORD_LETTERS.as_bytes()[idx] as char)
+            .collect::<String>()
+    });
 
     println!("webui password: {password}");
 
@@ -74,39 +131,29 @@ async fn main() -> std::io::Result<()> {
     };
 
     let state = Arc::new(parking_lot::Mutex::new({
-        let world = World::empty::<100, 50>(config);
-        ServerState {
-            paused: false,
-            target_tps: 0,
-            stats: SpeedMeasure::new(),
-            world,
-            password,
-            secret: instance_secret.clone(),
-            last_human_request: Instant::now(),
-        }
+        let world = World::with_dimensions(width, height, config);
+        ServerState::new(world, password, instance_secret.clone())
     }));
 
     {
         let state = state.clone();
 
+        let scheduler = scheduler::TickScheduler::from_env();
+        tracing::info!(threads = scheduler.threads(), "tick scheduler started");
+
         task::spawn_blocking(move || {
-            let mut tps = 0;
+            let mut delay = None;
             loop {
-                if tps != 0 {
-                    thread::sleep(Duration::from_millis(1000 / tps));
+                if let Some(delay) = delay {
+                    thread::sleep(delay);
                 }
 
-                let mut state = state.lock();
+                scheduler.run(vec![Box::new({
+                    let state = state.clone();
+                    move || tick_state(&state)
+                })]);
 
-                if !state.paused {
-                    let world = &mut state.world;
-                    world.tick();
-                }
-                if (Instant::now() - state.last_human_request).as_secs_f64() > 2f64 {
-                    tps = 0;
-                } else {
-                    tps = 30;
-                }
+                delay = state.lock().tick_delay(Instant::now());
             }
         })
     };
@@ -120,21 +167,38 @@ async fn main() -> std::io::Result<()> {
                 {
                     state.take_measure();
                 }
+                tracing::debug!(
+                    iteration = state.world.get_iteration(),
+                    population = state.world.count_alive(),
+                    tps = state.stats.measured_tps,
+                    "measure"
+                );
             }
         })
     };
 
     println!("http://127.0.0.1:8000/aquarium");
 
-    ctrlc::set_handler(move || {
-        println!("got SIGINT, exiting");
-        std::process::exit(0)
+    ctrlc::set_handler({
+        let state = state.clone();
+        move || {
+            println!("got SIGINT, exiting");
+            state.lock().census.flush();
+            std::process::exit(0)
+        }
     })
     .unwrap();
 
     HttpServer::new({
         let state = state.clone();
-        move || App::new().service(routes::build_routes(state.clone(), "aquarium"))
+        let limit = limiter::ConcurrencyLimit::from_env();
+        move || {
+            App::new().service(routes::build_routes(
+                state.clone(),
+                limit.clone(),
+                "aquarium",
+            ))
+        }
     })
     .bind(("0.0.0.0", 8000))?
     .run()
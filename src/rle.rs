@@ -0,0 +1,193 @@
+use std::fmt::Display;
+
+use crate::cells::{
+    organism::Organism,
+    world::{World, WorldCell},
+};
+
+/// cell kinds expressible in run-length encoded pattern
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RleCell {
+    /// `.`
+    Empty,
+    /// `d`, dead body holding `dead_energy`
+    Dead,
+    /// `o`, green organism, only read from patterns
+    Organism,
+}
+
+impl RleCell {
+    fn from_symbol(symbol: char) -> Option<Self> {
+        match symbol {
+            '.' => Some(RleCell::Empty),
+            'd' => Some(RleCell::Dead),
+            'o' => Some(RleCell::Organism),
+            _ => None,
+        }
+    }
+
+    fn symbol(self) -> char {
+        match self {
+            RleCell::Empty => '.',
+            RleCell::Dead => 'd',
+            RleCell::Organism => 'o',
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RleError {
+    UnknownSymbol(char),
+    TooWide { width: usize },
+    TooTall { height: usize },
+}
+
+impl Display for RleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RleError::UnknownSymbol(symbol) => write!(f, "unknown cell symbol: {:?}", symbol),
+            RleError::TooWide { width } => write!(f, "pattern is wider than {} cells", width),
+            RleError::TooTall { height } => write!(f, "pattern is taller than {} rows", height),
+        }
+    }
+}
+
+/// decodes pattern in the spirit of Life RLE into row-major cells of given field size:
+/// every symbol may be preceded by its repeat count, `$` ends a row, `!` ends the pattern
+/// and whitespace is ignored. Cells not covered by the pattern are empty
+pub fn decode_rle(pattern: &str, width: usize, height: usize) -> Result<Vec<RleCell>, RleError> {
+    let mut cells = vec![RleCell::Empty; width * height];
+    let (mut i, mut j) = (0usize, 0);
+    let mut count: Option<usize> = None;
+
+    for symbol in pattern.chars().filter(|symbol| !symbol.is_whitespace()) {
+        if let Some(digit) = symbol.to_digit(10) {
+            count = Some(
+                count
+                    .unwrap_or(0)
+                    .saturating_mul(10)
+                    .saturating_add(digit as usize),
+            );
+            continue;
+        }
+        let repeat = count.take().unwrap_or(1);
+        match symbol {
+            '!' => break,
+            '$' => {
+                i = i.saturating_add(repeat);
+                j = 0;
+            }
+            symbol => {
+                let cell = RleCell::from_symbol(symbol).ok_or(RleError::UnknownSymbol(symbol))?;
+                if repeat > width - j {
+                    return Err(RleError::TooWide { width });
+                }
+                // empty cells only move the cursor, so trailing rows may stay out of bounds
+                if cell != RleCell::Empty {
+                    if i >= height {
+                        return Err(RleError::TooTall { height });
+                    }
+                    cells[i * width + j..i * width + j + repeat].fill(cell);
+                }
+                j += repeat;
+            }
+        }
+    }
+
+    Ok(cells)
+}
+
+/// encodes dead bodies and empty cells of the world, organisms are written as empty cells
+pub fn encode_rle(world: &World) -> String {
+    let rows = (0..world.field.get_height()).map(|i| {
+        let mut runs: Vec<(usize, RleCell)> = vec![];
+        for j in 0..world.field.get_width() {
+            let cell = match world.field[(i, j)] {
+                WorldCell::DeadBody(..) => RleCell::Dead,
+                _ => RleCell::Empty,
+            };
+            match runs.last_mut() {
+                Some((count, last)) if *last == cell => *count += 1,
+                _ => runs.push((1, cell)),
+            }
+        }
+        if let Some((_, RleCell::Empty)) = runs.last() {
+            runs.pop();
+        }
+        runs.into_iter()
+            .map(|(count, cell)| match count {
+                1 => cell.symbol().to_string(),
+                count => format!("{}{}", count, cell.symbol()),
+            })
+            .collect::<String>()
+    });
+
+    let mut pattern = rows.collect::<Vec<_>>().join("$");
+    pattern.truncate(pattern.trim_end_matches('$').len());
+    pattern.push('!');
+    pattern
+}
+
+/// replaces the whole field with decoded pattern, fails without touching the field
+/// if pattern does not fit
+pub fn load_rle(world: &mut World, pattern: &str) -> Result<(), RleError> {
+    let (width, height) = (world.field.get_width(), world.field.get_height());
+    let cells = decode_rle(pattern, width, height)?;
+    let green = *Organism::green(0).get_program();
+
+    for (idx, cell) in cells.into_iter().enumerate() {
+        let pos = (idx / width, idx % width);
+        world.field[pos] = match cell {
+            RleCell::Dead => {
+                WorldCell::DeadBody(world.config.dead_energy, 0, world.get_iteration())
+            }
+            _ => WorldCell::Empty,
+        };
+        if cell == RleCell::Organism {
+            // cell has just been emptied, so spawning can not fail
+            world.spawn_program(pos, green, None).unwrap();
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode_rle, encode_rle, load_rle, RleCell, RleError};
+    use crate::cells::world::{World, WorldCell};
+
+    #[test]
+    fn test_rle_round_trip() {
+        // walled 3x3 chamber in the corner of the field
+        let pattern = "3d$d.d$3d!";
+        let mut world = World::empty::<5, 4>(Default::default());
+        world.populate_green(20);
+
+        load_rle(&mut world, pattern).unwrap();
+
+        assert_eq!(world.count_alive(), 0);
+        assert!(matches!(world.field[(1, 0)], WorldCell::DeadBody(..)));
+        assert!(matches!(world.field[(1, 1)], WorldCell::Empty));
+        assert!(matches!(world.field[(3, 0)], WorldCell::Empty));
+        assert_eq!(encode_rle(&world), pattern);
+    }
+
+    #[test]
+    fn test_rle_rejects_bad_patterns() {
+        assert_eq!(decode_rle("2.x!", 5, 5), Err(RleError::UnknownSymbol('x')));
+        assert_eq!(decode_rle("6d!", 5, 5), Err(RleError::TooWide { width: 5 }));
+        assert_eq!(
+            decode_rle("5$o!", 5, 5),
+            Err(RleError::TooTall { height: 5 })
+        );
+        assert_eq!(
+            decode_rle("o$ .o", 2, 2),
+            Ok(vec![
+                RleCell::Organism,
+                RleCell::Empty,
+                RleCell::Empty,
+                RleCell::Organism
+            ])
+        );
+    }
+}
@@ -1,25 +1,53 @@
-use actix_web::{dev::Service, web::Data, Scope};
+use std::{path::Path, time::Instant};
+
+use actix_web::{dev::Service, web::Data, HttpResponse, Scope};
 
 use actix_web::error::ErrorUnauthorized;
 
 use crate::{
     api,
+    limiter::ConcurrencyLimit,
     state::{AMState, MState},
 };
 
-pub fn build_routes(state: AMState, base_path: &str) -> Scope {
-    let react_app = actix_files::Files::new(".", "front/build").index_file("index.html");
+const STATIC_DIR: &str = "front/build";
+
+const MISSING_FRONTEND_MESSAGE: &str =
+    "frontend is not built, static files are missing. API is available under /api";
+
+/// `limit` is shared by all workers and caps number of api requests in flight
+pub fn build_routes(state: AMState, limit: ConcurrencyLimit, base_path: &str) -> Scope {
+    build_routes_with_static(state, limit, base_path, STATIC_DIR)
+}
 
+/// builds routes serving frontend from `static_dir`, falling back to plain-text
+/// notice when the directory does not exist
+fn build_routes_with_static(
+    state: AMState,
+    limit: ConcurrencyLimit,
+    base_path: &str,
+    static_dir: &str,
+) -> Scope {
     let state = actix_web::web::Data::from(state);
 
     let api_protected = Scope::new("")
         .service(api::pause)
         .service(api::spawn_random)
         .service(api::spawn_green)
+        .service(api::spawn_genome)
         .service(api::tick)
+        .service(api::step_cell)
         .service(api::set_setting)
+        .service(api::set_flag)
+        .service(api::set_gradient)
         .service(api::reset)
+        .service(api::reseed)
+        .service(api::paint_mutagen)
+        .service(api::clear_dead)
+        .service(api::disaster)
         .service(api::load_world)
+        .service(api::load_rle_pattern)
+        .service(api::import_state)
         .wrap_fn(|req, srv| {
             let accepted = {
                 let provided_token = req
@@ -27,9 +55,13 @@ pub fn build_routes(state: AMState, base_path: &str) -> Scope {
                     .map(|c| c.value().to_owned())
                     .unwrap_or_else(|| "none".to_string());
 
-                let state = req.app_data::<Data<MState>>().unwrap().lock();
-                let expected_token = &state.secret;
-                expected_token == &provided_token
+                let mut state = req.app_data::<Data<MState>>().unwrap().lock();
+                let accepted = state.secret == provided_token;
+                if accepted {
+                    // mutating calls count as human activity
+                    state.last_human_request = Instant::now();
+                }
+                accepted
             };
 
             let fut = srv.call(req);
@@ -46,13 +78,78 @@ pub fn build_routes(state: AMState, base_path: &str) -> Scope {
         .app_data(state)
         .service(api::auth)
         .service(api::get_map)
+        .service(api::get_map_sparse)
         .service(api::set_last_human)
         .service(api::inspect)
+        .service(api::inspect_json)
+        .service(api::get_genome)
+        .service(api::get_registers)
         .service(api::stats)
+        .service(api::history)
+        .service(api::dashboard)
+        .service(api::seed)
+        .service(api::reproduction_stats)
+        .service(api::energy_flow)
+        .service(api::birth_heatmap)
+        .service(api::death_heatmap)
+        .service(api::get_dominant_genome)
+        .service(api::get_lineage)
+        .service(api::memory)
+        .service(api::config_schema)
+        .service(api::flags)
+        .service(api::get_energy_histogram)
+        .service(api::get_minerals_histogram)
         .service(api::save_world)
-        .service(api_protected);
+        .service(api::save_rle_pattern)
+        .service(api::export_state)
+        .service(api_protected)
+        .wrap(limit);
+
+    let scope = Scope::new(base_path).service(api);
+
+    if Path::new(static_dir).is_dir() {
+        scope.default_service(actix_files::Files::new(".", static_dir).index_file("index.html"))
+    } else {
+        tracing::warn!("{static_dir} directory is missing, frontend will not be served");
+        scope.default_service(actix_web::web::to(|| async {
+            HttpResponse::Ok()
+                .content_type("text/plain")
+                .body(MISSING_FRONTEND_MESSAGE)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+
+    use super::{build_routes_with_static, MISSING_FRONTEND_MESSAGE};
+    use crate::{cells::world::World, limiter::ConcurrencyLimit, state::ServerState};
+
+    #[actix_web::test]
+    async fn test_missing_frontend_fallback() {
+        let state = Arc::new(parking_lot::Mutex::new(ServerState::new(
+            World::empty::<5, 5>(Default::default()),
+            "password".to_string(),
+            "secret".to_string(),
+        )));
+        let app = test::init_service(App::new().service(build_routes_with_static(
+            state,
+            ConcurrencyLimit::new(1),
+            "aquarium",
+            "no-such-directory",
+        )))
+        .await;
 
-    Scope::new(base_path)
-        .service(api)
-        .default_service(react_app)
+        let response = test::call_service(
+            &app,
+            test::TestRequest::get().uri("/aquarium/").to_request(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = test::read_body(response).await;
+        assert_eq!(body, MISSING_FRONTEND_MESSAGE.as_bytes());
+    }
 }
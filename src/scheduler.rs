@@ -0,0 +1,145 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Arc},
+    thread::{self, JoinHandle},
+};
+
+use parking_lot::Mutex;
+
+/// env variable holding number of tick worker threads, 1 by default
+pub const TICK_THREADS_VAR: &str = "AQUARIUM_TICK_THREADS";
+
+pub type TickJob = Box<dyn FnOnce() + Send>;
+
+/// fixed-size pool of named worker threads executing tick jobs,
+/// every world tick is submitted as a separate unit of work
+pub struct TickScheduler {
+    sender: Option<mpsc::Sender<TickJob>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl TickScheduler {
+    pub fn new(threads: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<TickJob>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let workers = (0..threads.max(1))
+            .map(|idx| {
+                let receiver = receiver.clone();
+                thread::Builder::new()
+                    .name(format!("tick-worker-{idx}"))
+                    .spawn(move || loop {
+                        let job = receiver.lock().recv();
+                        match job {
+                            // panicking job is reported by `run`, worker stays in the pool
+                            Ok(job) => {
+                                let _ = panic::catch_unwind(AssertUnwindSafe(job));
+                            }
+                            Err(_) => break,
+                        }
+                    })
+                    .expect("failed to spawn tick worker")
+            })
+            .collect();
+
+        TickScheduler {
+            sender: Some(sender),
+            workers,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let threads = std::env::var(TICK_THREADS_VAR)
+            .ok()
+            .and_then(|threads| threads.parse().ok())
+            .unwrap_or(1);
+        Self::new(threads)
+    }
+
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// dispatches jobs onto the pool and blocks until all of them are finished
+    pub fn run(&self, jobs: Vec<TickJob>) {
+        let (done_sender, done_receiver) = mpsc::channel();
+        let count = jobs.len();
+
+        for job in jobs {
+            let done_sender = done_sender.clone();
+            self.sender
+                .as_ref()
+                .unwrap()
+                .send(Box::new(move || {
+                    job();
+                    let _ = done_sender.send(());
+                }))
+                .expect("tick workers are gone");
+        }
+        // only senders moved into jobs are left, so a job dropping its sender
+        // without reporting (by panicking) ends the wait below
+        drop(done_sender);
+
+        for _ in 0..count {
+            if done_receiver.recv().is_err() {
+                tracing::error!("tick job panicked");
+                break;
+            }
+        }
+    }
+}
+
+impl Drop for TickScheduler {
+    fn drop(&mut self) {
+        self.sender.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Barrier,
+    };
+
+    use super::{TickJob, TickScheduler};
+
+    #[test]
+    fn test_scheduler_runs_jobs_on_configured_workers() {
+        let scheduler = TickScheduler::new(3);
+        assert_eq!(scheduler.threads(), 3);
+
+        // barrier is only passed when all three workers run jobs concurrently
+        let barrier = Arc::new(Barrier::new(3));
+        let ticks = Arc::new(AtomicUsize::new(0));
+        let jobs = (0..3)
+            .map(|_| {
+                let (barrier, ticks) = (barrier.clone(), ticks.clone());
+                Box::new(move || {
+                    barrier.wait();
+                    ticks.fetch_add(1, Ordering::Relaxed);
+                }) as TickJob
+            })
+            .collect();
+
+        scheduler.run(jobs);
+        assert_eq!(ticks.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn test_scheduler_survives_panicking_job() {
+        let scheduler = TickScheduler::new(1);
+        let ticks = Arc::new(AtomicUsize::new(0));
+
+        scheduler.run(vec![Box::new(|| panic!("job failed"))]);
+
+        let counter = ticks.clone();
+        scheduler.run(vec![Box::new(move || {
+            counter.fetch_add(1, Ordering::Relaxed);
+        })]);
+        assert_eq!(ticks.load(Ordering::Relaxed), 1);
+    }
+}
@@ -1,19 +1,56 @@
-use std::vec;
+use std::{collections::HashMap, vec};
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-use crate::cells::world::{World, WorldCell};
+use crate::cells::{
+    code::{OpCode, Program},
+    organism::Organism,
+    world::{World, WorldCell, WorldField},
+};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum SerializedCell {
-    Alive { energy: usize, minerals: usize },
-    Dead { energy: usize, minerals: usize },
+    Alive {
+        energy: usize,
+        minerals: usize,
+        /// energy relative to `max_cell_size`, may exceed 1
+        energy_fraction: f64,
+    },
+    Dead {
+        energy: usize,
+        minerals: usize,
+        age_since_death: usize,
+    },
     Empty,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SerializedWorld {
     cells: Vec<Vec<SerializedCell>>,
+    /// simulation step world was stored at
+    #[serde(default)]
+    iteration: usize,
+    #[serde(default)]
+    width: usize,
+    #[serde(default)]
+    height: usize,
+}
+
+fn store_cell(world: &World, cell: &WorldCell) -> SerializedCell {
+    match cell {
+        WorldCell::Empty => SerializedCell::Empty,
+        WorldCell::Organism(o) => SerializedCell::Alive {
+            energy: o.get_energy(),
+            minerals: o.get_minerals(),
+            energy_fraction: o.get_energy() as f64 / world.config.max_cell_size.max(1) as f64,
+        },
+        WorldCell::DeadBody(energy, minerals, died_at) => SerializedCell::Dead {
+            energy: *energy,
+            minerals: *minerals,
+            age_since_death: world.get_iteration().saturating_sub(*died_at),
+        },
+    }
 }
 
 pub fn store_world_shallow(world: &World) -> SerializedWorld {
@@ -23,21 +60,547 @@ pub fn store_world_shallow(world: &World) -> SerializedWorld {
         let mut row = vec![];
 
         for j in 0..world.field.get_width() {
-            let cell = match &world.field[(i, j)] {
-                WorldCell::Empty => SerializedCell::Empty,
-                WorldCell::Organism(o) => SerializedCell::Alive {
+            row.push(store_cell(world, &world.field[(i, j)]));
+        }
+        cells.push(row);
+    }
+
+    SerializedWorld {
+        cells,
+        iteration: world.get_iteration(),
+        width: world.field.get_width(),
+        height: world.field.get_height(),
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SparseCell {
+    pub i: usize,
+    pub j: usize,
+    pub cell: SerializedCell,
+}
+
+/// world representation with empty cells omitted
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SparseWorld {
+    pub width: usize,
+    pub height: usize,
+    pub cells: Vec<SparseCell>,
+}
+
+pub fn store_world_sparse(world: &World) -> SparseWorld {
+    let mut cells = vec![];
+
+    for i in 0..world.field.get_height() {
+        for j in 0..world.field.get_width() {
+            let cell = &world.field[(i, j)];
+            if !matches!(cell, WorldCell::Empty) {
+                cells.push(SparseCell {
+                    i,
+                    j,
+                    cell: store_cell(world, cell),
+                });
+            }
+        }
+    }
+
+    SparseWorld {
+        width: world.field.get_width(),
+        height: world.field.get_height(),
+        cells,
+    }
+}
+
+/// 3x3 block of cells centered at given position, rows outside of world are omitted
+pub fn store_neighbourhood(world: &World, (i, j): (usize, usize)) -> Vec<SparseCell> {
+    let (height, width) = (world.field.get_height(), world.field.get_width());
+    let mut cells = vec![];
+
+    for cell_i in i.saturating_sub(1)..(i + 2).min(height) {
+        for shift in [width - 1, 0, 1] {
+            let cell_j = (j + shift) % width;
+            cells.push(SparseCell {
+                i: cell_i,
+                j: cell_j,
+                cell: store_cell(world, &world.field[(cell_i, cell_j)]),
+            });
+        }
+    }
+
+    cells
+}
+
+/// organism state as shown by inspector, without program
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InspectedOrganism {
+    pub energy: usize,
+    pub minerals: usize,
+    pub can_clone: bool,
+    pub age: usize,
+    pub armor: usize,
+    pub lineage: usize,
+    pub label: Option<String>,
+    pub ip: usize,
+    pub registers: [u8; 16],
+}
+
+pub fn inspect_organism(organism: &Organism) -> InspectedOrganism {
+    InspectedOrganism {
+        energy: organism.get_energy(),
+        minerals: organism.get_minerals(),
+        can_clone: organism.can_clone,
+        age: organism.get_age(),
+        armor: organism.get_armor(),
+        lineage: organism.get_lineage(),
+        label: organism.get_label().map(|label| label.as_str().to_string()),
+        ip: organism.get_ip(),
+        registers: *organism.get_registers(),
+    }
+}
+
+/// registers and instruction pointer only, cheap to poll every tick
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RegistersSnapshot {
+    pub ip: usize,
+    pub registers: [u8; 16],
+}
+
+pub fn snapshot_registers(organism: &Organism) -> RegistersSnapshot {
+    RegistersSnapshot {
+        ip: organism.get_ip(),
+        registers: *organism.get_registers(),
+    }
+}
+
+/// complete program of organism together with its execution state,
+/// `program` is accepted back by spawn-genome
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrganismGenome {
+    pub program: Program,
+    pub registers: [u8; 16],
+    pub energy: usize,
+    pub minerals: usize,
+    pub ip: usize,
+}
+
+pub fn store_genome(organism: &Organism) -> OrganismGenome {
+    OrganismGenome {
+        program: *organism.get_program(),
+        registers: *organism.get_registers(),
+        energy: organism.get_energy(),
+        minerals: organism.get_minerals(),
+        ip: organism.get_ip(),
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializedGenome {
+    pub position: (usize, usize),
+    pub energy: usize,
+    pub minerals: usize,
+    pub program: Program,
+}
+
+pub fn store_genomes(world: &World) -> Vec<SerializedGenome> {
+    let mut genomes = vec![];
+
+    for i in 0..world.field.get_height() {
+        for j in 0..world.field.get_width() {
+            if let WorldCell::Organism(o) = &world.field[(i, j)] {
+                genomes.push(SerializedGenome {
+                    position: (i, j),
                     energy: o.get_energy(),
                     minerals: o.get_minerals(),
-                },
-                WorldCell::DeadBody(energy, minerals) => SerializedCell::Dead {
-                    energy: *energy,
-                    minerals: *minerals,
-                },
-            };
-            row.push(cell);
+                    program: *o.get_program(),
+                });
+            }
+        }
+    }
+
+    genomes
+}
+
+/// save marked with `compact` flag holds only instructions reachable from
+/// organism's instruction pointer, see [store_field_compact]
+#[derive(Serialize, Deserialize)]
+struct CompactSave {
+    compact: bool,
+    field: Value,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CompactProgram {
+    genes: Vec<(u8, OpCode)>,
+}
+
+/// stores field replacing every program with its reachable genes,
+/// unreachable genes are lost and restored as no-ops on load
+pub fn store_field_compact(field: &WorldField) -> serde_json::Result<Value> {
+    let mut value = serde_json::to_value(field)?;
+
+    if let Some(cells) = value.get_mut("inner").and_then(Value::as_array_mut) {
+        for (cell, stored) in field.inner.iter().zip(cells.iter_mut()) {
+            if let (WorldCell::Organism(o), Some(stored)) = (cell, stored.get_mut("Organism")) {
+                stored["code"] = serde_json::to_value(CompactProgram {
+                    genes: o.get_program().reachable_genes(o.get_ip()),
+                })?;
+            }
         }
-        cells.push(row);
     }
 
-    SerializedWorld { cells }
+    serde_json::to_value(CompactSave {
+        compact: true,
+        field: value,
+    })
+}
+
+/// loads field saved either fully or with [store_field_compact]
+pub fn load_field(bytes: &[u8]) -> serde_json::Result<WorldField> {
+    let value: Value = serde_json::from_slice(bytes)?;
+    if value.get("compact") != Some(&Value::Bool(true)) {
+        return serde_json::from_value(value);
+    }
+
+    let mut field = serde_json::from_value::<CompactSave>(value)?.field;
+    if let Some(cells) = field.get_mut("inner").and_then(Value::as_array_mut) {
+        for stored in cells.iter_mut().filter_map(|cell| cell.get_mut("Organism")) {
+            let code = stored
+                .get_mut("code")
+                .map(Value::take)
+                .ok_or_else(|| serde::de::Error::custom("organism without code"))?;
+            let compact: CompactProgram = serde_json::from_value(code)?;
+            stored["code"] = serde_json::to_value(Program::from_genes(&compact.genes))?;
+        }
+    }
+    serde_json::from_value(field)
+}
+
+/// most widespread exact program among living organisms
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DominantGenome {
+    pub population: usize,
+    pub disassembly: String,
+}
+
+pub fn dominant_genome(world: &World) -> Option<DominantGenome> {
+    let mut clusters: HashMap<&Program, usize> = HashMap::new();
+    for organism in living_organisms(world) {
+        *clusters.entry(organism.get_program()).or_default() += 1;
+    }
+
+    clusters
+        .into_iter()
+        .max_by_key(|(_, population)| *population)
+        .map(|(program, population)| DominantGenome {
+            population,
+            disassembly: program.print_minimized(0),
+        })
+}
+
+/// aggregate state of living members of a single lineage
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LineageStats {
+    pub lineage: usize,
+    pub population: usize,
+    pub mean_energy: f64,
+    pub mean_age: f64,
+    /// mean row and column of members
+    pub centroid: (f64, f64),
+    /// disassembly of the most widespread program within lineage
+    pub representative_genome: String,
+}
+
+pub fn lineage_stats(world: &World, lineage: usize) -> Option<LineageStats> {
+    let (mut population, mut energy, mut age, mut rows, mut columns) = (0, 0, 0, 0, 0);
+    let mut clusters: HashMap<&Program, usize> = HashMap::new();
+
+    for (idx, cell) in world.field.inner.iter().enumerate() {
+        match cell {
+            WorldCell::Organism(o) if o.get_lineage() == lineage => {
+                population += 1;
+                energy += o.get_energy();
+                age += o.get_age();
+                rows += idx / world.field.get_width();
+                columns += idx % world.field.get_width();
+                *clusters.entry(o.get_program()).or_default() += 1;
+            }
+            _ => {}
+        }
+    }
+
+    let representative = clusters
+        .into_iter()
+        .max_by_key(|(_, population)| *population)?
+        .0;
+    let mean = |total: usize| total as f64 / population as f64;
+    Some(LineageStats {
+        lineage,
+        population,
+        mean_energy: mean(energy),
+        mean_age: mean(age),
+        centroid: (mean(rows), mean(columns)),
+        representative_genome: representative.print_minimized(0),
+    })
+}
+
+/// counts of values falling into `bins` equal buckets covering `0..max`,
+/// values at or above `max` are counted in the last bucket
+pub fn histogram(values: impl Iterator<Item = usize>, bins: usize, max: usize) -> Vec<usize> {
+    let mut counts = vec![0; bins];
+    if bins == 0 {
+        return counts;
+    }
+    let max = max.max(1);
+    for value in values {
+        let bin = (value.min(max) * bins / max).min(bins - 1);
+        counts[bin] += 1;
+    }
+    counts
+}
+
+fn living_organisms(world: &World) -> impl Iterator<Item = &Organism> {
+    world.field.inner.iter().filter_map(|cell| match cell {
+        WorldCell::Organism(o) => Some(o.as_ref()),
+        _ => None,
+    })
+}
+
+pub fn energy_histogram(world: &World, bins: usize) -> Vec<usize> {
+    histogram(
+        living_organisms(world).map(Organism::get_energy),
+        bins,
+        world.config.max_cell_size,
+    )
+}
+
+pub fn minerals_histogram(world: &World, bins: usize) -> Vec<usize> {
+    histogram(
+        living_organisms(world).map(Organism::get_minerals),
+        bins,
+        world.config.max_minerals,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{distributions::Bernoulli, thread_rng};
+
+    use super::{
+        dominant_genome, energy_histogram, inspect_organism, lineage_stats, load_field,
+        snapshot_registers, store_field_compact, store_world_shallow, store_world_sparse,
+        SerializedCell, SerializedWorld,
+    };
+    use crate::cells::{
+        code::Program,
+        organism::Organism,
+        world::{World, WorldCell, WorldConfig, WorldField},
+    };
+
+    #[test]
+    fn test_sparse_world_is_smaller() {
+        let mut world = World::empty::<100, 50>(Default::default());
+        world.populate_green(5);
+
+        let sparse = store_world_sparse(&world);
+        assert_eq!(sparse.cells.len(), 5);
+        assert_eq!((sparse.width, sparse.height), (100, 50));
+
+        let sparse_size = serde_json::to_vec(&sparse).unwrap().len();
+        let full_size = serde_json::to_vec(&store_world_shallow(&world))
+            .unwrap()
+            .len();
+        assert!(sparse_size * 10 < full_size);
+    }
+
+    #[test]
+    fn test_inspected_organism_has_minerals() {
+        let mut bot = Organism::green(100);
+        bot.add_minerals(42, 100);
+
+        let inspected = inspect_organism(&bot);
+        assert_eq!(inspected.minerals, 42);
+        assert!(!inspected.can_clone);
+    }
+
+    #[test]
+    fn test_energy_histogram() {
+        let config = WorldConfig {
+            max_cell_size: 100,
+            ..Default::default()
+        };
+        let mut world = World::empty::<5, 5>(config);
+        for (idx, energy) in [0, 10, 24, 25, 60, 99, 100, 500].into_iter().enumerate() {
+            world.field.inner[idx] = WorldCell::Organism(Box::new(Organism::green(energy)));
+        }
+        world.field.inner[10] = WorldCell::DeadBody(50, 0, 0);
+
+        assert_eq!(energy_histogram(&world, 4), vec![3, 1, 1, 3]);
+        assert_eq!(energy_histogram(&world, 1), vec![8]);
+        assert!(energy_histogram(&world, 0).is_empty());
+    }
+
+    #[test]
+    fn test_dominant_genome() {
+        let mut world = World::empty::<5, 5>(Default::default());
+        assert!(dominant_genome(&world).is_none());
+
+        let mut rng = thread_rng();
+        for idx in 0..3 {
+            world.field.inner[idx] = WorldCell::Organism(Box::new(Organism::green(100)));
+        }
+        for idx in 3..5 {
+            let program = Program::random_program(&mut rng);
+            world.field.inner[idx] =
+                WorldCell::Organism(Box::new(Organism::with_program(100, 0, program)));
+        }
+
+        let dominant = dominant_genome(&world).unwrap();
+        assert_eq!(dominant.population, 3);
+        assert_eq!(
+            dominant.disassembly,
+            Organism::green(0).get_program().print_minimized(0)
+        );
+    }
+
+    #[test]
+    fn test_compact_save_preserves_behaviour() {
+        let config = WorldConfig {
+            aging_mutation_freq: Bernoulli::new(0.0).unwrap(),
+            ..Default::default()
+        };
+        let mut original = World::seeded::<10, 10>(config.clone(), 7);
+        let mut rng = thread_rng();
+        for idx in (0..100).step_by(7) {
+            let bot = Organism::with_program(300, 10, Program::random_program(&mut rng));
+            original.field.inner[idx] = WorldCell::Organism(Box::new(bot));
+        }
+
+        let compact = serde_json::to_vec(&store_field_compact(&original.field).unwrap()).unwrap();
+        let full = serde_json::to_vec(&original.field).unwrap();
+        assert!(compact.len() < full.len());
+
+        let mut loaded = World::seeded::<10, 10>(config, 7);
+        loaded.field = load_field(&compact).unwrap();
+        assert_eq!(loaded.count_alive(), original.count_alive());
+
+        original.tick();
+        loaded.tick();
+        for (a, b) in original.field.inner.iter().zip(loaded.field.inner.iter()) {
+            match (a, b) {
+                (WorldCell::Organism(a), WorldCell::Organism(b)) => {
+                    assert_eq!(a.get_energy(), b.get_energy());
+                    assert_eq!(
+                        serde_json::to_value(snapshot_registers(a)).unwrap(),
+                        serde_json::to_value(snapshot_registers(b)).unwrap()
+                    );
+                }
+                (WorldCell::Empty, WorldCell::Empty)
+                | (WorldCell::DeadBody(..), WorldCell::DeadBody(..)) => {}
+                _ => panic!("worlds diverged"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_rendering_hints() {
+        let config = WorldConfig {
+            max_cell_size: 400,
+            ..Default::default()
+        };
+        let mut world = World::empty::<5, 5>(config);
+        world.field[(1, 1)] = WorldCell::Organism(Box::new(Organism::green(100)));
+        world.field[(3, 3)] = WorldCell::DeadBody(10, 0, world.get_iteration());
+        for _ in 0..3 {
+            world.tick();
+        }
+
+        let sparse = store_world_sparse(&world);
+        let cell = |pos: (usize, usize)| {
+            sparse
+                .cells
+                .iter()
+                .find(|cell| (cell.i, cell.j) == pos)
+                .map(|cell| cell.cell.clone())
+        };
+        match cell((3, 3)) {
+            Some(SerializedCell::Dead {
+                age_since_death, ..
+            }) => assert_eq!(age_since_death, 3),
+            other => panic!("expected dead body, got {:?}", other),
+        }
+
+        let energy = match &world.field[(1, 1)] {
+            WorldCell::Organism(o) => o.get_energy(),
+            _ => panic!("organism is gone"),
+        };
+        match cell((1, 1)) {
+            Some(SerializedCell::Alive {
+                energy_fraction, ..
+            }) => assert_eq!(energy_fraction, energy as f64 / 400.0),
+            other => panic!("expected organism, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_serialized_world_metadata() {
+        let mut world = World::empty::<7, 3>(Default::default());
+        world.tick();
+        world.tick();
+
+        let stored = store_world_shallow(&world);
+        assert_eq!(
+            (stored.iteration, stored.width, stored.height),
+            (world.get_iteration(), 7, 3)
+        );
+
+        // payloads predating metadata are still accepted
+        let old: SerializedWorld = serde_json::from_str(r#"{"cells": [["Empty"]]}"#).unwrap();
+        assert_eq!((old.iteration, old.width, old.height), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_compact_load_rejects_malformed_organism() {
+        for organism in ["5", r#"{"energy": 10}"#] {
+            let save = format!(
+                r#"{{"compact": true, "field": {{"inner": [{{"Organism": {organism}}}]}}}}"#
+            );
+            assert!(load_field(save.as_bytes()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_dead_body_without_death_iteration_loads() {
+        let mut world = World::empty::<2, 1>(Default::default());
+        world.field[(0, 0)] = WorldCell::DeadBody(10, 5, 7);
+        let stored = serde_json::to_string(&world.field).unwrap();
+        assert!(stored.contains("[10,5,7]"));
+
+        let loaded = load_field(stored.replace("[10,5,7]", "[10,5]").as_bytes()).unwrap();
+        assert!(matches!(loaded[(0, 0)], WorldCell::DeadBody(10, 5, 0)));
+
+        let encoded = bincode::serialize(&world.field).unwrap();
+        let decoded: WorldField = bincode::deserialize(&encoded).unwrap();
+        assert!(matches!(decoded[(0, 0)], WorldCell::DeadBody(10, 5, 7)));
+    }
+
+    #[test]
+    fn test_lineage_stats_exclude_other_lineages() {
+        let mut world = World::empty::<5, 5>(Default::default());
+        let place = |world: &mut World, pos, energy, lineage| {
+            let mut bot = Organism::green(energy);
+            bot.set_lineage(lineage);
+            world.field[pos] = WorldCell::Organism(Box::new(bot));
+        };
+        place(&mut world, (0, 0), 100, 1);
+        place(&mut world, (2, 4), 50, 1);
+        place(&mut world, (4, 4), 1000, 2);
+
+        let stats = lineage_stats(&world, 1).unwrap();
+        assert_eq!(stats.population, 2);
+        assert_eq!(stats.mean_energy, 75.0);
+        assert_eq!(stats.centroid, (1.0, 2.0));
+
+        assert_eq!(lineage_stats(&world, 2).unwrap().population, 1);
+        assert!(lineage_stats(&world, 3).is_none());
+    }
 }
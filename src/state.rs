@@ -1,6 +1,18 @@
-use std::{collections::HashMap, sync::Arc, time::Instant};
+use std::{
+    collections::{HashMap, VecDeque},
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use crate::cells::world::World;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bottleneck::BottleneckMonitor,
+    cells::world::{World, WorldSnapshot},
+    census::{CensusLog, CensusStream, CENSUS_FILE},
+    serialization::{store_world_sparse, SparseWorld},
+};
 
 pub type AMState = Arc<MState>;
 
@@ -42,18 +54,224 @@ impl SpeedMeasure {
     }
 }
 
+#[derive(Clone, Debug, Serialize)]
+pub struct StatsResponse {
+    pub measured_tps: f64,
+    pub is_paused: bool,
+    pub iteration: usize,
+    pub population: usize,
+    pub day_phase: Option<usize>,
+    pub is_night: bool,
+}
+
+/// tick rate simulation is throttled to while someone is watching it
+const HUMAN_TPS: u64 = 30;
+
+/// number of per-second measurements kept in history
+const HISTORY_LENGTH: usize = 60;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HistoryPoint {
+    pub iteration: usize,
+    pub population: usize,
+    pub measured_tps: f64,
+}
+
+/// stats, sparse world and history gathered under a single lock
+#[derive(Clone, Debug, Serialize)]
+pub struct Dashboard {
+    pub stats: StatsResponse,
+    pub world: SparseWorld,
+    pub history: Vec<HistoryPoint>,
+}
+
+/// whole server state transferable between machines, see [ServerState::export]
+#[derive(Serialize, Deserialize)]
+pub struct ExportedState {
+    pub world: WorldSnapshot,
+    pub paused: bool,
+    pub measured_tps: f64,
+}
+
 pub struct ServerState {
     pub paused: bool,
-    pub target_tps: u64,
     pub stats: SpeedMeasure,
     pub world: World,
+    pub bottleneck: BottleneckMonitor,
+    pub census: CensusLog,
+    /// JSON Lines census, see [crate::census::CENSUS_STREAM_VAR]
+    pub census_stream: Option<CensusStream>,
+    /// recent measurements, oldest first
+    pub history: VecDeque<HistoryPoint>,
     pub password: String,
     pub secret: String,
     pub last_human_request: Instant,
 }
 
 impl ServerState {
+    pub fn new(world: World, password: String, secret: String) -> Self {
+        ServerState {
+            paused: false,
+            stats: SpeedMeasure::new(),
+            world,
+            bottleneck: BottleneckMonitor::new(),
+            census: CensusLog::new(Path::new(CENSUS_FILE)),
+            census_stream: CensusStream::from_env(),
+            history: VecDeque::with_capacity(HISTORY_LENGTH),
+            password,
+            secret,
+            last_human_request: Instant::now(),
+        }
+    }
+
     pub fn take_measure(&mut self) {
-        self.stats.take_measure(&mut self.world)
+        self.stats.take_measure(&mut self.world);
+
+        if self.history.len() == HISTORY_LENGTH {
+            self.history.pop_front();
+        }
+        self.history.push_back(HistoryPoint {
+            iteration: self.world.get_iteration(),
+            population: self.world.count_alive(),
+            measured_tps: self.stats.measured_tps,
+        });
+    }
+
+    pub fn stats_response(&self) -> StatsResponse {
+        StatsResponse {
+            measured_tps: self.stats.measured_tps,
+            is_paused: self.paused,
+            iteration: self.world.get_iteration(),
+            population: self.world.count_alive(),
+            day_phase: self.world.day_phase(),
+            is_night: self.world.is_night(),
+        }
+    }
+
+    /// pause between ticks, simulation runs unthrottled once nobody has interacted
+    /// with it for `idle_timeout_secs` or when `always_run` is set
+    pub fn tick_delay(&self, now: Instant) -> Option<Duration> {
+        let config = &self.world.config;
+        let idle = now.saturating_duration_since(self.last_human_request)
+            > Duration::from_secs(config.idle_timeout_secs as u64);
+        if config.always_run || idle {
+            None
+        } else {
+            Some(Duration::from_millis(1000 / HUMAN_TPS))
+        }
+    }
+
+    pub fn dashboard(&self) -> Dashboard {
+        Dashboard {
+            stats: self.stats_response(),
+            world: store_world_sparse(&self.world),
+            history: self.history.iter().cloned().collect(),
+        }
+    }
+
+    pub fn export(&self) -> Result<Vec<u8>, bincode::Error> {
+        bincode::serialize(&ExportedState {
+            world: self.world.snapshot(),
+            paused: self.paused,
+            measured_tps: self.stats.measured_tps,
+        })
+    }
+
+    /// restores state produced by [ServerState::export], state is left
+    /// untouched if data fails to deserialize or describes inconsistent world
+    pub fn import(&mut self, data: &[u8]) -> Result<(), bincode::Error> {
+        let exported: ExportedState = bincode::deserialize(data)?;
+        World::validate_snapshot(&exported.world).map_err(bincode::ErrorKind::Custom)?;
+        self.world.restore(exported.world);
+        self.paused = exported.paused;
+        self.stats.measured_tps = exported.measured_tps;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use super::{ExportedState, ServerState};
+    use crate::cells::world::World;
+
+    #[test]
+    fn test_tick_delay() {
+        let mut state = ServerState::new(
+            World::empty::<5, 5>(Default::default()),
+            String::new(),
+            String::new(),
+        );
+        let now = state.last_human_request;
+        assert!(state.tick_delay(now).is_some());
+        assert!(state.tick_delay(now + Duration::from_secs(3)).is_none());
+
+        state.world.config.idle_timeout_secs = 10;
+        assert!(state.tick_delay(now + Duration::from_secs(3)).is_some());
+
+        state.world.config.always_run = true;
+        assert!(state.tick_delay(now).is_none());
+        assert!(state.tick_delay(Instant::now()).is_none());
+    }
+
+    #[test]
+    fn test_stats_fields_are_typed() {
+        let mut world = World::empty::<10, 10>(Default::default());
+        world.populate_green(5);
+        let mut state = ServerState::new(world, String::new(), String::new());
+        state.paused = true;
+
+        let json = serde_json::to_value(state.stats_response()).unwrap();
+        assert!(json["measured_tps"].is_number());
+        assert_eq!(json["is_paused"], true);
+        assert_eq!(json["iteration"], 1);
+        assert_eq!(json["population"], 5);
+    }
+
+    #[test]
+    fn test_export_import_roundtrip() {
+        let mut world = World::seeded::<10, 10>(Default::default(), 7);
+        world.populate_random(20);
+        world.populate_green(20);
+        for _ in 0..10 {
+            world.tick();
+        }
+        let state = ServerState::new(world, String::new(), String::new());
+        let data = state.export().unwrap();
+
+        let mut fresh = ServerState::new(
+            World::empty::<10, 10>(Default::default()),
+            String::new(),
+            String::new(),
+        );
+        fresh.import(&data).unwrap();
+
+        assert_eq!(fresh.world.checksum(), state.world.checksum());
+        assert_eq!(fresh.world.get_iteration(), state.world.get_iteration());
+
+        assert!(fresh.import(&data[..data.len() / 2]).is_err());
+        assert_eq!(fresh.world.checksum(), state.world.checksum());
+    }
+
+    #[test]
+    fn test_import_rejects_inconsistent_snapshot() {
+        let mut state = ServerState::new(
+            World::empty::<10, 10>(Default::default()),
+            String::new(),
+            String::new(),
+        );
+        let mut world = state.world.snapshot();
+        world.field.inner.pop();
+        let data = bincode::serialize(&ExportedState {
+            world,
+            paused: false,
+            measured_tps: 0.0,
+        })
+        .unwrap();
+
+        let checksum = state.world.checksum();
+        assert!(state.import(&data).is_err());
+        assert_eq!(state.world.checksum(), checksum);
     }
 }